@@ -5,6 +5,7 @@ use anchor_lang::Discriminator;
 use anchor_spl::token::{self, Burn, Mint, MintTo, Token, TokenAccount, Transfer};
 use borsh::BorshSerialize;
 use solana_program::bpf_loader_upgradeable::{self, UpgradeableLoaderState};
+use solana_program::hash::hashv;
 use solana_program::instruction::{AccountMeta, Instruction};
 use solana_program::program_option::COption;
 
@@ -20,6 +21,9 @@ const STAKE_SEED: &[u8] = b"stake";
 const LEVEL_CONFIG_SEED: &[u8] = b"level_config";
 const HP_SCALE_SEED: &[u8] = b"hp_scale";
 const RIG_BUFF_CONFIG_SEED: &[u8] = b"rig_buff";
+const PENDING_ADMIN_SEED: &[u8] = b"pending_admin";
+const LEVEL_BONUS_TABLE_SEED: &[u8] = b"level_bonus_table";
+const DELEGATE_SEED: &[u8] = b"delegate";
 const METADATA_NAME_MAX: usize = 32;
 const METADATA_SYMBOL_MAX: usize = 10;
 const METADATA_URI_MAX: usize = 200;
@@ -164,11 +168,25 @@ const CLAIM_MAX_BASE_AMOUNT: u128 = 500 * MIND_DECIMALS as u128; // cap single c
 const MAX_MINING_DT_SECONDS: u64 = 86_400; // cap catch-up window to 1 day
 const MINING_ACC_DELTA_CAP_BPS: u64 = 5_000; // max +50% acc_mind_per_hp per update
 const RENEW_WINDOW_DAYS: u64 = 3;
-const STAKING_SHARE_BPS: u128 = 3_000; // 30%
+const STAKING_SHARE_BPS: u16 = 3_000; // 30%, default for `Config::staking_share_bps`
 const BADGE_BONUS_CAP_BPS: u16 = 2_000; // 20%
+const MAX_BADGE_PROOF_LEN: usize = 24;
 const LEVEL_BONUS_CAP_BPS: u16 = 1_000; // 10%
+const LEVEL_BONUS_LEVELS: usize = 7;
+const DEFAULT_LEVEL_BONUSES: [u16; LEVEL_BONUS_LEVELS] = [0, 0, 160, 340, 550, 780, 1_000];
+/// `level_bonus_bps` of a position last written before positions recorded their bonus.
+const LEVEL_BONUS_UNRECORDED: u16 = u16::MAX;
+const DELEGATE_PERMISSION_CLAIM: u8 = 1 << 0;
+const DELEGATE_PERMISSION_RENEW: u8 = 1 << 1;
+const DELEGATE_PERMISSION_STAKE: u8 = 1 << 2;
+const DELEGATE_PERMISSION_ALL: u8 =
+    DELEGATE_PERMISSION_CLAIM | DELEGATE_PERMISSION_RENEW | DELEGATE_PERMISSION_STAKE;
 const UNSTAKE_BURN_BPS: u128 = 600; // 6%
 const STAKING_EPOCH_DAYS: u64 = 14;
+const CRANK_EPOCH_DAYS: u64 = 7;
+const MAX_EPOCH_ROLLERS: usize = 4;
+const MAX_BATCH_CLAIM_POSITIONS: usize = 8;
+const MAX_CRANK_DEACTIVATE_POSITIONS: usize = 8;
 const XNT_BASE: u64 = 1_000_000_000;
 const MIND_DECIMALS: u64 = 1_000_000_000;
 const MIND_DECIMALS_U8: u8 = 9;
@@ -182,6 +200,10 @@ pub mod mining_v2 {
 
     pub fn init_config(ctx: Context<InitConfig>, params: InitConfigParams) -> Result<()> {
         require!(params.emission_per_sec > 0, ErrorCode::InvalidAmount);
+        require!(
+            params.emission_per_sec <= EMISSION_PER_SEC_MAX,
+            ErrorCode::EmissionTooHigh
+        );
         require!(params.max_effective_hp > 0, ErrorCode::InvalidAmount);
         let seconds_per_day = if params.seconds_per_day == 0 {
             SECONDS_PER_DAY_DEFAULT
@@ -189,6 +211,15 @@ pub mod mining_v2 {
             params.seconds_per_day
         };
         require!(seconds_per_day > 0, ErrorCode::InvalidAmount);
+        let staking_share_bps = if params.staking_share_bps == 0 {
+            STAKING_SHARE_BPS
+        } else {
+            params.staking_share_bps
+        };
+        require!(
+            staking_share_bps as u128 <= BPS_DENOMINATOR,
+            ErrorCode::InvalidStakingShareBps
+        );
         assert_upgrade_authority(&ctx.accounts.program_data, ctx.accounts.admin.key())?;
 
         require!(
@@ -229,6 +260,28 @@ pub mod mining_v2 {
         config.staking_undistributed_xnt = 0;
         config.staking_accounted_balance = 0;
         config.bumps = bumps;
+        config.auto_roll_epoch_seconds = 0;
+        config.forfeit_expired_rewards = false;
+        config.forfeit_window_seconds = 0;
+        config.unstake_cooldown_sec = 0;
+        config.treasury_sweep_threshold = 0;
+        config.treasury_destination = Pubkey::default();
+        config.staking_xnt_vault = Pubkey::default();
+        config.max_consecutive_renews = 0;
+        config.staker_emission_bps = 0;
+        config.staking_acc_mind_per_mind = 0;
+        config.epoch_rollers = [Pubkey::default(); MAX_EPOCH_ROLLERS];
+        config.max_mint_per_claim = u64::MAX;
+        config.staking_paused = false;
+        config.reward_round_half_up = false;
+        config.paused = false;
+        config.epoch_seq = 0;
+        config.staking_lock_days = 0;
+        config.badge_merkle_root = [0; 32];
+        config.crank_reward = 0;
+        config.min_position_hp = 0;
+        config.staking_share_bps = staking_share_bps;
+        config.level_bonus_table_initialized = false;
 
         ctx.accounts.staking_reward_vault.bump = *ctx.bumps.get("staking_reward_vault").unwrap();
         ctx.accounts.treasury_vault.bump = *ctx.bumps.get("treasury_vault").unwrap();
@@ -287,6 +340,7 @@ pub mod mining_v2 {
         buff.mind_treasury_vault = ctx.accounts.mind_treasury_vault.key();
         buff.mind_per_hp_per_day = params.mind_per_hp_per_day;
         buff.bump = *ctx.bumps.get("rig_buff_config").unwrap();
+        buff.min_buff_upgrade_interval_sec = 0;
         Ok(())
     }
 
@@ -295,12 +349,28 @@ pub mod mining_v2 {
         params: UpdateRigBuffConfigParams,
     ) -> Result<()> {
         require!(params.mind_per_hp_per_day > 0, ErrorCode::InvalidAmount);
+        let info = ctx.accounts.rig_buff_config.to_account_info();
+        let mut buff = load_rig_buff_config_any(&info)?;
         require_keys_eq!(
-            ctx.accounts.rig_buff_config.admin,
+            ctx.accounts.config.admin,
             ctx.accounts.admin.key(),
             ErrorCode::Unauthorized
         );
-        ctx.accounts.rig_buff_config.mind_per_hp_per_day = params.mind_per_hp_per_day;
+        // Keep the stored admin in step with `config.admin` after a transfer.
+        buff.admin = ctx.accounts.config.admin;
+        buff.mind_per_hp_per_day = params.mind_per_hp_per_day;
+        buff.min_buff_upgrade_interval_sec = params.min_buff_upgrade_interval_sec;
+
+        // Accounts created before the cooldown field existed are grown in place.
+        resize_program_account(
+            &info,
+            &ctx.accounts.admin.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            8 + RigBuffConfig::INIT_SPACE,
+        )?;
+        let mut data = info.try_borrow_mut_data()?;
+        let mut cursor: &mut [u8] = &mut data;
+        buff.try_serialize(&mut cursor)?;
         Ok(())
     }
 
@@ -309,8 +379,13 @@ pub mod mining_v2 {
         contract_type: u8,
         position_index: u64,
     ) -> Result<()> {
+        let bonus_table = load_level_bonus_table(
+            ctx.accounts.level_bonus_table.as_deref(),
+            &ctx.accounts.config,
+        )?;
         let now = Clock::get()?.unix_timestamp;
         let cfg = &mut ctx.accounts.config;
+        require!(!cfg.paused, ErrorCode::ProtocolPaused);
         update_mining_global(cfg, now)?;
 
         let bump = *ctx.bumps.get("user_profile").unwrap();
@@ -328,12 +403,17 @@ pub mod mining_v2 {
             ErrorCode::Unauthorized
         );
         update_user_xp(&mut profile, now)?;
+        sync_profile_level_bonus(cfg, &mut profile, now, bonus_table.as_ref())?;
         require!(
             position_index == profile.next_position_index,
             ErrorCode::InvalidPositionIndex
         );
 
         let (duration_days, base_hp_scaled, cost_base) = contract_terms(contract_type)?;
+        require!(
+            base_hp_scaled >= cfg.min_position_hp,
+            ErrorCode::PositionHpTooLow
+        );
         let seconds_per_day = cfg.seconds_per_day;
         let duration_seconds = (duration_days as i64)
             .checked_mul(seconds_per_day as i64)
@@ -354,7 +434,8 @@ pub mod mining_v2 {
 
         let buff_bps: u16 = 0;
         let buffed_hp_scaled = apply_bps(base_hp_scaled as u128, buff_bps)?;
-        let hp_effective = effective_hp_scaled(base_hp_scaled as u128, profile.level, buff_bps)?;
+        let hp_effective =
+            effective_hp_with_bonus(base_hp_scaled as u128, buff_bps, profile.level_bonus_bps)?;
         let reward_debt = earned_per_hp(hp_effective, cfg.acc_mind_per_hp)?;
         let position = &mut ctx.accounts.position;
         position.owner = ctx.accounts.owner.key();
@@ -373,6 +454,9 @@ pub mod mining_v2 {
         position.expired = false;
         position.buff_applied_from_cycle = 0;
         position.last_level_applied = profile.level;
+        position.level_bonus_bps = profile.level_bonus_bps;
+        position.consecutive_renews = 0;
+        position.last_buff_upgrade_ts = 0;
 
         increase_profile_hp(&mut profile, base_hp_scaled as u128, buffed_hp_scaled)?;
         let hp_effective_u64 = u64::try_from(hp_effective).map_err(|_| ErrorCode::MathOverflow)?;
@@ -381,14 +465,8 @@ pub mod mining_v2 {
             .checked_add(hp_effective_u64)
             .ok_or(ErrorCode::MathOverflow)?;
 
-        let staking_share = (cost_base as u128)
-            .checked_mul(STAKING_SHARE_BPS)
-            .ok_or(ErrorCode::MathOverflow)?
-            .checked_div(BPS_DENOMINATOR)
-            .ok_or(ErrorCode::MathOverflow)? as u64;
-        let treasury_share = cost_base
-            .checked_sub(staking_share)
-            .ok_or(ErrorCode::MathOverflow)?;
+        let (staking_share, treasury_share) =
+            split_contract_cost(cost_base, cfg.staking_share_bps)?;
 
         system_program::transfer(
             CpiContext::new(
@@ -411,14 +489,17 @@ pub mod mining_v2 {
                 ),
                 staking_share,
             )?;
-            cfg.staking_undistributed_xnt = cfg
-                .staking_undistributed_xnt
-                .checked_add(staking_share)
-                .ok_or(ErrorCode::MathOverflow)?;
-            cfg.staking_accounted_balance = cfg
-                .staking_accounted_balance
-                .checked_add(staking_share)
-                .ok_or(ErrorCode::MathOverflow)?;
+            // In SPL mode the lamport share is not reward funding; it stays withdrawable by admin.
+            if xnt_is_native(cfg) {
+                cfg.staking_undistributed_xnt = cfg
+                    .staking_undistributed_xnt
+                    .checked_add(staking_share)
+                    .ok_or(ErrorCode::MathOverflow)?;
+                cfg.staking_accounted_balance = cfg
+                    .staking_accounted_balance
+                    .checked_add(staking_share)
+                    .ok_or(ErrorCode::MathOverflow)?;
+            }
         }
 
         profile.next_position_index = profile
@@ -438,13 +519,24 @@ pub mod mining_v2 {
     }
 
     pub fn renew_rig(ctx: Context<RenewRig>) -> Result<()> {
+        let authority = owner_or_delegate(
+            &ctx.accounts.owner,
+            ctx.accounts.delegate.as_ref(),
+            ctx.accounts.delegate_record.as_deref(),
+            DELEGATE_PERMISSION_RENEW,
+        )?;
+        let bonus_table = load_level_bonus_table(
+            ctx.accounts.level_bonus_table.as_deref(),
+            &ctx.accounts.config,
+        )?;
         let now = Clock::get()?.unix_timestamp;
         let cfg = &mut ctx.accounts.config;
+        require!(!cfg.paused, ErrorCode::ProtocolPaused);
         let mut position = load_position_any(&ctx.accounts.position)?;
         let bump = *ctx.bumps.get("user_profile").unwrap();
         let mut profile = ensure_user_profile_v2(
             &ctx.accounts.user_profile,
-            &ctx.accounts.owner.to_account_info(),
+            &authority,
             &ctx.accounts.system_program,
             ctx.accounts.owner.key(),
             bump,
@@ -458,12 +550,20 @@ pub mod mining_v2 {
         );
         update_user_xp(&mut profile, now)?;
         let rig_type = position_rig_type(&position, cfg)?;
-        apply_level_updates_to_position(&mut position, &profile, rig_type, now)?;
-        apply_pending_buff(cfg, &mut position, profile.level, now, &mut profile)?;
+        apply_level_updates_to_position(
+            &mut position,
+            &profile,
+            rig_type,
+            now,
+            cfg.acc_mind_per_hp,
+            bonus_table.as_ref(),
+        )?;
+        apply_pending_buff(cfg, &mut position, now, &mut profile)?;
 
         let grace_deadline = grace_deadline_ts(position.end_ts, cfg.seconds_per_day)?;
         require!(now >= position.end_ts, ErrorCode::PositionRenewTooEarly);
         require!(now <= grace_deadline, ErrorCode::PositionGraceExpired);
+        check_renew_cap(cfg, &mut position)?;
 
         let (duration_days, base_hp_scaled, cost_base) = contract_terms(rig_type)?;
         let duration_seconds = (duration_days as i64)
@@ -489,10 +589,17 @@ pub mod mining_v2 {
             position.deactivated = false;
         } else {
             if !position.expired {
-                expire_position(cfg, &mut position, &mut profile, now)?;
+                expire_position(
+                    cfg,
+                    &mut position,
+                    ctx.accounts.position.key(),
+                    &mut profile,
+                    now,
+                )?;
             } else {
                 update_mining_global(cfg, now)?;
             }
+            sync_profile_level_bonus(cfg, &mut profile, now, bonus_table.as_ref())?;
 
             let new_active_hp = profile
                 .active_hp
@@ -523,9 +630,10 @@ pub mod mining_v2 {
             let buff_bps = position_buff_bps(&position, rig_type, now);
             let buffed_hp_scaled = apply_bps(base_hp_scaled as u128, buff_bps)?;
             let hp_effective =
-                effective_hp_scaled(base_hp_scaled as u128, profile.level, buff_bps)?;
+                effective_hp_with_bonus(base_hp_scaled as u128, buff_bps, profile.level_bonus_bps)?;
             position.reward_debt = earned_per_hp(hp_effective, cfg.acc_mind_per_hp)?;
             position.last_level_applied = profile.level;
+            position.level_bonus_bps = profile.level_bonus_bps;
 
             increase_profile_hp(&mut profile, base_hp_scaled as u128, buffed_hp_scaled)?;
             let hp_effective_u64 =
@@ -537,26 +645,16 @@ pub mod mining_v2 {
         }
 
         let system_program = ctx.accounts.system_program.to_account_info();
-        ensure_position_v2(
-            &ctx.accounts.position,
-            &ctx.accounts.owner.to_account_info(),
-            &system_program,
-        )?;
+        ensure_position_v2(&ctx.accounts.position, &authority, &system_program)?;
 
-        let staking_share = (cost_base as u128)
-            .checked_mul(STAKING_SHARE_BPS)
-            .ok_or(ErrorCode::MathOverflow)?
-            .checked_div(BPS_DENOMINATOR)
-            .ok_or(ErrorCode::MathOverflow)? as u64;
-        let treasury_share = cost_base
-            .checked_sub(staking_share)
-            .ok_or(ErrorCode::MathOverflow)?;
+        let (staking_share, treasury_share) =
+            split_contract_cost(cost_base, cfg.staking_share_bps)?;
 
         system_program::transfer(
             CpiContext::new(
                 ctx.accounts.system_program.to_account_info(),
                 SystemTransfer {
-                    from: ctx.accounts.owner.to_account_info(),
+                    from: authority.clone(),
                     to: ctx.accounts.treasury_vault.to_account_info(),
                 },
             ),
@@ -567,20 +665,23 @@ pub mod mining_v2 {
                 CpiContext::new(
                     ctx.accounts.system_program.to_account_info(),
                     SystemTransfer {
-                        from: ctx.accounts.owner.to_account_info(),
+                        from: authority.clone(),
                         to: ctx.accounts.staking_reward_vault.to_account_info(),
                     },
                 ),
                 staking_share,
             )?;
-            cfg.staking_undistributed_xnt = cfg
-                .staking_undistributed_xnt
-                .checked_add(staking_share)
-                .ok_or(ErrorCode::MathOverflow)?;
-            cfg.staking_accounted_balance = cfg
-                .staking_accounted_balance
-                .checked_add(staking_share)
-                .ok_or(ErrorCode::MathOverflow)?;
+            // In SPL mode the lamport share is not reward funding; it stays withdrawable by admin.
+            if xnt_is_native(cfg) {
+                cfg.staking_undistributed_xnt = cfg
+                    .staking_undistributed_xnt
+                    .checked_add(staking_share)
+                    .ok_or(ErrorCode::MathOverflow)?;
+                cfg.staking_accounted_balance = cfg
+                    .staking_accounted_balance
+                    .checked_add(staking_share)
+                    .ok_or(ErrorCode::MathOverflow)?;
+            }
         }
 
         save_position(&ctx.accounts.position, &position)?;
@@ -589,8 +690,29 @@ pub mod mining_v2 {
     }
 
     pub fn renew_rig_with_buff(ctx: Context<RenewRigWithBuff>) -> Result<()> {
+        let bonus_table = load_level_bonus_table(
+            ctx.accounts.level_bonus_table.as_deref(),
+            &ctx.accounts.config,
+        )?;
+        let buff_config = load_rig_buff_config_any(&ctx.accounts.rig_buff_config)?;
         let now = Clock::get()?.unix_timestamp;
         let cfg = &mut ctx.accounts.config;
+        require!(!cfg.paused, ErrorCode::ProtocolPaused);
+        require_keys_eq!(
+            buff_config.mind_mint,
+            cfg.mind_mint,
+            ErrorCode::InvalidRigBuffConfig
+        );
+        require_keys_eq!(
+            ctx.accounts.burn_mind_vault.key(),
+            buff_config.mind_burn_vault,
+            ErrorCode::InvalidRigBuffConfig
+        );
+        require_keys_eq!(
+            ctx.accounts.treasury_mind_vault.key(),
+            buff_config.mind_treasury_vault,
+            ErrorCode::InvalidRigBuffConfig
+        );
         let mut position = load_position_any(&ctx.accounts.position)?;
         let bump = *ctx.bumps.get("user_profile").unwrap();
         let mut profile = ensure_user_profile_v2(
@@ -609,13 +731,21 @@ pub mod mining_v2 {
         );
         update_user_xp(&mut profile, now)?;
         let rig_type = position_rig_type(&position, cfg)?;
-        apply_level_updates_to_position(&mut position, &profile, rig_type, now)?;
-        apply_pending_buff(cfg, &mut position, profile.level, now, &mut profile)?;
+        apply_level_updates_to_position(
+            &mut position,
+            &profile,
+            rig_type,
+            now,
+            cfg.acc_mind_per_hp,
+            bonus_table.as_ref(),
+        )?;
+        apply_pending_buff(cfg, &mut position, now, &mut profile)?;
 
         let renew_window_start = renew_window_start_ts(position.end_ts, cfg.seconds_per_day)?;
         let grace_deadline = grace_deadline_ts(position.end_ts, cfg.seconds_per_day)?;
         require!(now >= renew_window_start, ErrorCode::PositionRenewTooEarly);
         require!(now <= grace_deadline, ErrorCode::PositionGraceExpired);
+        check_renew_cap(cfg, &mut position)?;
 
         let (duration_days, base_hp_scaled, cost_base) = contract_terms(rig_type)?;
         let duration_seconds = (duration_days as i64)
@@ -631,14 +761,45 @@ pub mod mining_v2 {
                 .checked_add(1)
                 .ok_or(ErrorCode::MathOverflow)?;
         }
+        let min_interval = buff_config.min_buff_upgrade_interval_sec;
+        if new_buff_level > position.buff_level && min_interval > 0 {
+            // Positions upgraded before the timestamp existed fall back to the pending-buff
+            // timestamp, or to the cycle start.
+            let last_upgrade_ts = if position.last_buff_upgrade_ts != 0 {
+                position.last_buff_upgrade_ts
+            } else if position.buff_applied_from_cycle != 0 {
+                position.buff_applied_from_cycle as i64
+            } else {
+                position.start_ts
+            };
+            let elapsed = now.saturating_sub(last_upgrade_ts);
+            let min_interval_i64 =
+                i64::try_from(min_interval).map_err(|_| ErrorCode::MathOverflow)?;
+            require!(elapsed >= min_interval_i64, ErrorCode::BuffUpgradeCooldown);
+        }
 
         if is_early {
             update_mining_global(cfg, now)?;
         } else if !position.expired {
-            expire_position(cfg, &mut position, &mut profile, now)?;
+            expire_position(
+                cfg,
+                &mut position,
+                ctx.accounts.position.key(),
+                &mut profile,
+                now,
+            )?;
         } else {
             update_mining_global(cfg, now)?;
         }
+        sync_profile_level_bonus(cfg, &mut profile, now, bonus_table.as_ref())?;
+        apply_level_updates_to_position(
+            &mut position,
+            &profile,
+            rig_type,
+            now,
+            cfg.acc_mind_per_hp,
+            bonus_table.as_ref(),
+        )?;
 
         let system_program = ctx.accounts.system_program.to_account_info();
         ensure_position_v2(
@@ -656,6 +817,10 @@ pub mod mining_v2 {
         } else {
             position.buff_applied_from_cycle
         };
+        if new_buff_level > position.buff_level {
+            // The cooldown runs from when the upgrade takes effect.
+            position.last_buff_upgrade_ts = if is_early { old_end_ts } else { now };
+        }
 
         if is_early {
             position.end_ts = position
@@ -697,9 +862,10 @@ pub mod mining_v2 {
             let buff_bps = position_buff_bps(&position, rig_type, now);
             let buffed_hp_scaled = apply_bps(base_hp_scaled as u128, buff_bps)?;
             let hp_effective =
-                effective_hp_scaled(base_hp_scaled as u128, profile.level, buff_bps)?;
+                effective_hp_with_bonus(base_hp_scaled as u128, buff_bps, profile.level_bonus_bps)?;
             position.reward_debt = earned_per_hp(hp_effective, cfg.acc_mind_per_hp)?;
             position.last_level_applied = profile.level;
+            position.level_bonus_bps = profile.level_bonus_bps;
 
             increase_profile_hp(&mut profile, base_hp_scaled as u128, buffed_hp_scaled)?;
             let hp_effective_u64 =
@@ -710,14 +876,8 @@ pub mod mining_v2 {
                 .ok_or(ErrorCode::MathOverflow)?;
         }
 
-        let staking_share = (cost_base as u128)
-            .checked_mul(STAKING_SHARE_BPS)
-            .ok_or(ErrorCode::MathOverflow)?
-            .checked_div(BPS_DENOMINATOR)
-            .ok_or(ErrorCode::MathOverflow)? as u64;
-        let treasury_share = cost_base
-            .checked_sub(staking_share)
-            .ok_or(ErrorCode::MathOverflow)?;
+        let (staking_share, treasury_share) =
+            split_contract_cost(cost_base, cfg.staking_share_bps)?;
 
         system_program::transfer(
             CpiContext::new(
@@ -740,14 +900,17 @@ pub mod mining_v2 {
                 ),
                 staking_share,
             )?;
-            cfg.staking_undistributed_xnt = cfg
-                .staking_undistributed_xnt
-                .checked_add(staking_share)
-                .ok_or(ErrorCode::MathOverflow)?;
-            cfg.staking_accounted_balance = cfg
-                .staking_accounted_balance
-                .checked_add(staking_share)
-                .ok_or(ErrorCode::MathOverflow)?;
+            // In SPL mode the lamport share is not reward funding; it stays withdrawable by admin.
+            if xnt_is_native(cfg) {
+                cfg.staking_undistributed_xnt = cfg
+                    .staking_undistributed_xnt
+                    .checked_add(staking_share)
+                    .ok_or(ErrorCode::MathOverflow)?;
+                cfg.staking_accounted_balance = cfg
+                    .staking_accounted_balance
+                    .checked_add(staking_share)
+                    .ok_or(ErrorCode::MathOverflow)?;
+            }
         }
 
         save_position(&ctx.accounts.position, &position)?;
@@ -756,13 +919,24 @@ pub mod mining_v2 {
     }
 
     pub fn claim_mind(ctx: Context<ClaimMind>) -> Result<()> {
+        let authority = owner_or_delegate(
+            &ctx.accounts.owner,
+            ctx.accounts.delegate.as_ref(),
+            ctx.accounts.delegate_record.as_deref(),
+            DELEGATE_PERMISSION_CLAIM,
+        )?;
+        let bonus_table = load_level_bonus_table(
+            ctx.accounts.level_bonus_table.as_deref(),
+            &ctx.accounts.config,
+        )?;
         let now = Clock::get()?.unix_timestamp;
         let cfg = &mut ctx.accounts.config;
+        require!(!cfg.paused, ErrorCode::ProtocolPaused);
         let mut position = load_position_any(&ctx.accounts.position)?;
         let bump = *ctx.bumps.get("user_profile").unwrap();
         let mut profile = ensure_user_profile_v2(
             &ctx.accounts.user_profile,
-            &ctx.accounts.owner.to_account_info(),
+            &authority,
             &ctx.accounts.system_program,
             ctx.accounts.owner.key(),
             bump,
@@ -781,20 +955,49 @@ pub mod mining_v2 {
         );
         update_user_xp(&mut profile, now)?;
         let rig_type = position_rig_type(&position, cfg)?;
-        apply_level_updates_to_position(&mut position, &profile, rig_type, now)?;
-        apply_pending_buff(cfg, &mut position, profile.level, now, &mut profile)?;
+        apply_level_updates_to_position(
+            &mut position,
+            &profile,
+            rig_type,
+            now,
+            cfg.acc_mind_per_hp,
+            bonus_table.as_ref(),
+        )?;
+        apply_pending_buff(cfg, &mut position, now, &mut profile)?;
 
         if !position.deactivated && !position.expired && now >= position.end_ts {
-            expire_position(cfg, &mut position, &mut profile, now)?;
+            expire_position(
+                cfg,
+                &mut position,
+                ctx.accounts.position.key(),
+                &mut profile,
+                now,
+            )?;
         } else {
             update_mining_global(cfg, now)?;
         }
+        // Expiry above settles under the old bonus; the table change applies from here on.
+        sync_profile_level_bonus(cfg, &mut profile, now, bonus_table.as_ref())?;
+        apply_level_updates_to_position(
+            &mut position,
+            &profile,
+            rig_type,
+            now,
+            cfg.acc_mind_per_hp,
+            bonus_table.as_ref(),
+        )?;
 
         let (hp_effective, acc_used) = effective_hp_for_claim(&position, profile.level, cfg, now)?;
-        let pending = pending_mind(hp_effective, acc_used, position.reward_debt)?;
+        let pending = pending_mind(
+            hp_effective,
+            acc_used,
+            position.reward_debt,
+            cfg.reward_round_half_up,
+        )?;
         require!(pending > 0, ErrorCode::NothingToClaim);
-        require!(pending <= CLAIM_MAX_BASE_AMOUNT, ErrorCode::ClaimTooLarge);
-        let reward = u64::try_from(pending).map_err(|_| ErrorCode::MathOverflow)?;
+        let capped = pending.min(cfg.max_mint_per_claim as u128);
+        require!(capped <= CLAIM_MAX_BASE_AMOUNT, ErrorCode::ClaimTooLarge);
+        let reward = u64::try_from(capped).map_err(|_| ErrorCode::MathOverflow)?;
 
         let signer_seeds: &[&[u8]] = &[VAULT_SEED, &[cfg.bumps.vault_authority]];
         token::mint_to(
@@ -810,14 +1013,15 @@ pub mod mining_v2 {
             reward,
         )?;
 
-        position.reward_debt = earned_per_hp(hp_effective, acc_used)?;
+        // Whatever the per-claim ceiling held back stays pending for the next claim.
+        let carried = pending.checked_sub(capped).ok_or(ErrorCode::MathOverflow)?;
+        position.reward_debt =
+            earned_per_hp_rounded(hp_effective, acc_used, cfg.reward_round_half_up)?
+                .checked_sub(carried)
+                .ok_or(ErrorCode::MathOverflow)?;
         position.last_level_applied = profile.level;
         let system_program = ctx.accounts.system_program.to_account_info();
-        ensure_position_v2(
-            &ctx.accounts.position,
-            &ctx.accounts.owner.to_account_info(),
-            &system_program,
-        )?;
+        ensure_position_v2(&ctx.accounts.position, &authority, &system_program)?;
         save_position(&ctx.accounts.position, &position)?;
         save_user_profile(&ctx.accounts.user_profile, &profile)?;
 
@@ -828,7 +1032,111 @@ pub mod mining_v2 {
         Ok(())
     }
 
+    /// Claims every position in `remaining_accounts` (writable, owned by the signer) with a
+    /// single mint. Each position is settled exactly as `claim_mind` would settle it.
+    pub fn batch_claim_mind<'info>(
+        ctx: Context<'_, '_, '_, 'info, BatchClaimMind<'info>>,
+    ) -> Result<()> {
+        let bonus_table = load_level_bonus_table(
+            ctx.accounts.level_bonus_table.as_deref(),
+            &ctx.accounts.config,
+        )?;
+        require!(
+            ctx.remaining_accounts.len() <= MAX_BATCH_CLAIM_POSITIONS,
+            ErrorCode::TooManyPositions
+        );
+        let now = Clock::get()?.unix_timestamp;
+        let cfg = &mut ctx.accounts.config;
+        require!(!cfg.paused, ErrorCode::ProtocolPaused);
+        let owner = ctx.accounts.owner.key();
+        let bump = *ctx.bumps.get("user_profile").unwrap();
+        let mut profile = ensure_user_profile_v2(
+            &ctx.accounts.user_profile,
+            &ctx.accounts.owner.to_account_info(),
+            &ctx.accounts.system_program,
+            owner,
+            bump,
+            now,
+        )?;
+        require_keys_eq!(profile.owner, owner, ErrorCode::Unauthorized);
+        update_user_xp(&mut profile, now)?;
+        update_mining_global(cfg, now)?;
+        sync_profile_level_bonus(cfg, &mut profile, now, bonus_table.as_ref())?;
+
+        let owner_info = ctx.accounts.owner.to_account_info();
+        let system_program = ctx.accounts.system_program.to_account_info();
+        let mut total: u128 = 0;
+        for info in ctx.remaining_accounts.iter() {
+            require!(info.is_writable, ErrorCode::InvalidPositionSize);
+            let mut position = load_position_any(info)?;
+            require_keys_eq!(position.owner, owner, ErrorCode::Unauthorized);
+            let rig_type = position_rig_type(&position, cfg)?;
+            apply_level_updates_to_position(
+                &mut position,
+                &profile,
+                rig_type,
+                now,
+                cfg.acc_mind_per_hp,
+                bonus_table.as_ref(),
+            )?;
+            apply_pending_buff(cfg, &mut position, now, &mut profile)?;
+            if !position.deactivated && !position.expired && now >= position.end_ts {
+                expire_position(cfg, &mut position, info.key(), &mut profile, now)?;
+            }
+
+            let (hp_effective, acc_used) =
+                effective_hp_for_claim(&position, profile.level, cfg, now)?;
+            let pending = pending_mind(
+                hp_effective,
+                acc_used,
+                position.reward_debt,
+                cfg.reward_round_half_up,
+            )?;
+            let budget = (cfg.max_mint_per_claim as u128).saturating_sub(total);
+            let capped = pending.min(budget);
+            require!(capped <= CLAIM_MAX_BASE_AMOUNT, ErrorCode::ClaimTooLarge);
+            total = total.checked_add(capped).ok_or(ErrorCode::MathOverflow)?;
+
+            let carried = pending.checked_sub(capped).ok_or(ErrorCode::MathOverflow)?;
+            position.reward_debt =
+                earned_per_hp_rounded(hp_effective, acc_used, cfg.reward_round_half_up)?
+                    .checked_sub(carried)
+                    .ok_or(ErrorCode::MathOverflow)?;
+            position.last_level_applied = profile.level;
+            ensure_position_v2(info, &owner_info, &system_program)?;
+            save_position(info, &position)?;
+        }
+        require!(total > 0, ErrorCode::NothingToClaim);
+        let total_amount = u64::try_from(total).map_err(|_| ErrorCode::MathOverflow)?;
+
+        let signer_seeds: &[&[u8]] = &[VAULT_SEED, &[cfg.bumps.vault_authority]];
+        token::mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                MintTo {
+                    mint: ctx.accounts.mind_mint.to_account_info(),
+                    to: ctx.accounts.user_mind_ata.to_account_info(),
+                    authority: ctx.accounts.vault_authority.to_account_info(),
+                },
+                &[signer_seeds],
+            ),
+            total_amount,
+        )?;
+        save_user_profile(&ctx.accounts.user_profile, &profile)?;
+
+        emit!(BatchMindClaimed {
+            owner,
+            total_amount,
+            position_count: ctx.remaining_accounts.len() as u8,
+        });
+        Ok(())
+    }
+
     pub fn deactivate_position(ctx: Context<DeactivatePosition>) -> Result<()> {
+        let bonus_table = load_level_bonus_table(
+            ctx.accounts.level_bonus_table.as_deref(),
+            &ctx.accounts.config,
+        )?;
         let now = Clock::get()?.unix_timestamp;
         let cfg = &mut ctx.accounts.config;
         let mut position = load_position_any(&ctx.accounts.position)?;
@@ -850,8 +1158,15 @@ pub mod mining_v2 {
         )?;
         update_user_xp(&mut profile, now)?;
         let rig_type = position_rig_type(&position, cfg)?;
-        apply_level_updates_to_position(&mut position, &profile, rig_type, now)?;
-        apply_pending_buff(cfg, &mut position, profile.level, now, &mut profile)?;
+        apply_level_updates_to_position(
+            &mut position,
+            &profile,
+            rig_type,
+            now,
+            cfg.acc_mind_per_hp,
+            bonus_table.as_ref(),
+        )?;
+        apply_pending_buff(cfg, &mut position, now, &mut profile)?;
 
         require!(now >= position.end_ts, ErrorCode::PositionNotExpired);
         if position.deactivated {
@@ -860,13 +1175,14 @@ pub mod mining_v2 {
         let grace_deadline = grace_deadline_ts(position.end_ts, cfg.seconds_per_day)?;
         require!(now > grace_deadline, ErrorCode::PositionInGrace);
 
-        if !position.expired {
-            expire_position(cfg, &mut position, &mut profile, now)?;
-        } else {
-            update_mining_global(cfg, now)?;
-        }
-
-        finalize_position(cfg, &mut position, &mut profile, now)?;
+        deactivate_position_state(
+            cfg,
+            &mut position,
+            ctx.accounts.position.key(),
+            &mut profile,
+            now,
+        )?;
+        sync_profile_level_bonus(cfg, &mut profile, now, bonus_table.as_ref())?;
         let system_program = ctx.accounts.system_program.to_account_info();
         ensure_position_v2(
             &ctx.accounts.position,
@@ -879,6 +1195,10 @@ pub mod mining_v2 {
     }
 
     pub fn level_up(ctx: Context<LevelUp>) -> Result<()> {
+        let bonus_table = load_level_bonus_table(
+            ctx.accounts.level_bonus_table.as_deref(),
+            &ctx.accounts.config,
+        )?;
         let now = Clock::get()?.unix_timestamp;
         let cfg = &mut ctx.accounts.config;
         if !LEVELING_ENABLED {
@@ -902,6 +1222,7 @@ pub mod mining_v2 {
         );
 
         update_user_xp(&mut profile, now)?;
+        sync_profile_level_bonus(cfg, &mut profile, now, bonus_table.as_ref())?;
 
         require!(profile.level < 6, ErrorCode::MaxLevelReached);
         let next_level = profile
@@ -918,8 +1239,8 @@ pub mod mining_v2 {
             ErrorCode::InsufficientLevelUpFunds
         );
         let buffed_total_scaled = profile.active_hp as u128;
-        let old_bonus = level_bonus_bps(profile.level);
-        let new_bonus = level_bonus_bps(next_level);
+        let old_bonus = profile.level_bonus_bps;
+        let new_bonus = level_bonus_for(next_level, bonus_table.as_ref());
         let effective_before = apply_bps(buffed_total_scaled, old_bonus)?;
         let effective_after = apply_bps(buffed_total_scaled, new_bonus)?;
         let delta_effective = effective_after
@@ -934,6 +1255,7 @@ pub mod mining_v2 {
                 .ok_or(ErrorCode::MathOverflow)?;
         }
         profile.level_acc_snapshots[next_level as usize] = cfg.acc_mind_per_hp;
+        profile.level_bonus_bps = new_bonus;
 
         let burn_amount = cost.checked_div(2).ok_or(ErrorCode::MathOverflow)?;
         let treasury_amount = cost
@@ -1029,15 +1351,24 @@ pub mod mining_v2 {
     }
 
     pub fn stake_mind(ctx: Context<StakeMind>, amount: u64) -> Result<()> {
+        let authority = owner_or_delegate(
+            &ctx.accounts.owner,
+            ctx.accounts.delegate.as_ref(),
+            ctx.accounts.delegate_record.as_deref(),
+            DELEGATE_PERMISSION_STAKE,
+        )?;
         require!(amount > 0, ErrorCode::InvalidAmount);
         let now = Clock::get()?.unix_timestamp;
         let cfg = &mut ctx.accounts.config;
+        require!(!cfg.paused, ErrorCode::ProtocolPaused);
+        require!(!cfg.staking_paused, ErrorCode::StakingPaused);
         update_staking_global(cfg, now)?;
+        settle_staker_emission(cfg, now)?;
 
         let bump = *ctx.bumps.get("user_profile").unwrap();
         let profile = ensure_user_profile_v2(
             &ctx.accounts.user_profile,
-            &ctx.accounts.owner.to_account_info(),
+            &authority,
             &ctx.accounts.system_program,
             ctx.accounts.owner.key(),
             bump,
@@ -1055,6 +1386,11 @@ pub mod mining_v2 {
             ctx.accounts.user_stake.reward_debt = 0;
             ctx.accounts.user_stake.reward_owed = 0;
             ctx.accounts.user_stake.bump = *ctx.bumps.get("user_stake").unwrap();
+            ctx.accounts.user_stake.mind_reward_debt = 0;
+            ctx.accounts.user_stake.mind_reward_owed = 0;
+            ctx.accounts.user_stake.stake_start_ts = now;
+            ctx.accounts.user_stake.pending_unstake = 0;
+            ctx.accounts.user_stake.unstake_available_ts = 0;
         }
         require_keys_eq!(
             ctx.accounts.user_stake.owner,
@@ -1063,6 +1399,7 @@ pub mod mining_v2 {
         );
 
         accrue_staking_owed(cfg, &mut ctx.accounts.user_stake)?;
+        accrue_staked_mind_owed(cfg, &mut ctx.accounts.user_stake)?;
 
         token::transfer(
             CpiContext::new(
@@ -1070,26 +1407,33 @@ pub mod mining_v2 {
                 Transfer {
                     from: ctx.accounts.owner_mind_ata.to_account_info(),
                     to: ctx.accounts.staking_mind_vault.to_account_info(),
-                    authority: ctx.accounts.owner.to_account_info(),
+                    authority: authority.clone(),
                 },
             ),
             amount,
         )?;
 
+        let lock_seconds = stake_lock_seconds(cfg)?;
+        ctx.accounts.user_stake.stake_start_ts =
+            weighted_stake_start_ts(&ctx.accounts.user_stake, amount, now, lock_seconds)?;
         ctx.accounts.user_stake.staked_mind = ctx
             .accounts
             .user_stake
             .staked_mind
             .checked_add(amount)
-            .ok_or(ErrorCode::MathOverflow)?;
+            .ok_or(ErrorCode::StakingMathOverflow)?;
         cfg.staking_total_staked_mind = cfg
             .staking_total_staked_mind
             .checked_add(amount)
-            .ok_or(ErrorCode::MathOverflow)?;
+            .ok_or(ErrorCode::StakingMathOverflow)?;
         ctx.accounts.user_stake.reward_debt = earned_per_stake(
             ctx.accounts.user_stake.staked_mind,
             cfg.staking_acc_xnt_per_mind,
         )?;
+        ctx.accounts.user_stake.mind_reward_debt = earned_per_stake(
+            ctx.accounts.user_stake.staked_mind,
+            cfg.staking_acc_mind_per_mind,
+        )?;
 
         emit!(MindStaked {
             owner: ctx.accounts.owner.key(),
@@ -1102,35 +1446,18 @@ pub mod mining_v2 {
         require!(amount > 0, ErrorCode::InvalidAmount);
         let now = Clock::get()?.unix_timestamp;
         let cfg = &mut ctx.accounts.config;
-        update_staking_global(cfg, now)?;
-
+        require!(!cfg.paused, ErrorCode::ProtocolPaused);
+        require!(!cfg.staking_paused, ErrorCode::StakingPaused);
         require!(
-            ctx.accounts.user_stake.staked_mind >= amount,
-            ErrorCode::InsufficientStake
+            cfg.unstake_cooldown_sec == 0,
+            ErrorCode::UnstakeCooldownEnabled
         );
+        check_stake_lock(cfg, &ctx.accounts.user_stake, now)?;
+        update_staking_global(cfg, now)?;
+        settle_staker_emission(cfg, now)?;
 
-        accrue_staking_owed(cfg, &mut ctx.accounts.user_stake)?;
-
-        ctx.accounts.user_stake.staked_mind = ctx
-            .accounts
-            .user_stake
-            .staked_mind
-            .checked_sub(amount)
-            .ok_or(ErrorCode::MathOverflow)?;
-        cfg.staking_total_staked_mind = cfg
-            .staking_total_staked_mind
-            .checked_sub(amount)
-            .ok_or(ErrorCode::MathOverflow)?;
-
-        let burn_amount = (amount as u128)
-            .checked_mul(UNSTAKE_BURN_BPS)
-            .ok_or(ErrorCode::MathOverflow)?
-            .checked_div(BPS_DENOMINATOR)
-            .ok_or(ErrorCode::MathOverflow)?;
-        let burn_amount = u64::try_from(burn_amount).map_err(|_| ErrorCode::MathOverflow)?;
-        let transfer_amount = amount
-            .checked_sub(burn_amount)
-            .ok_or(ErrorCode::MathOverflow)?;
+        let (burn_amount, transfer_amount) =
+            remove_stake(cfg, &mut ctx.accounts.user_stake, amount)?;
 
         let signer_seeds: &[&[u8]] = &[VAULT_SEED, &[cfg.bumps.vault_authority]];
         if burn_amount > 0 {
@@ -1162,11 +1489,6 @@ pub mod mining_v2 {
             )?;
         }
 
-        ctx.accounts.user_stake.reward_debt = earned_per_stake(
-            ctx.accounts.user_stake.staked_mind,
-            cfg.staking_acc_xnt_per_mind,
-        )?;
-
         emit!(MindUnstaked {
             owner: ctx.accounts.owner.key(),
             amount,
@@ -1174,124 +1496,449 @@ pub mod mining_v2 {
         Ok(())
     }
 
-    pub fn claim_xnt(ctx: Context<ClaimXnt>) -> Result<()> {
+    pub fn request_unstake(ctx: Context<RequestUnstake>, amount: u64) -> Result<()> {
+        require!(amount > 0, ErrorCode::InvalidAmount);
         let now = Clock::get()?.unix_timestamp;
         let cfg = &mut ctx.accounts.config;
-        update_staking_global(cfg, now)?;
-
-        let bump = *ctx.bumps.get("user_profile").unwrap();
-        let profile = ensure_user_profile_v2(
-            &ctx.accounts.user_profile,
-            &ctx.accounts.owner.to_account_info(),
-            &ctx.accounts.system_program,
-            ctx.accounts.owner.key(),
-            bump,
-            now,
-        )?;
-        require_keys_eq!(
-            profile.owner,
-            ctx.accounts.owner.key(),
-            ErrorCode::Unauthorized
+        require!(!cfg.paused, ErrorCode::ProtocolPaused);
+        require!(!cfg.staking_paused, ErrorCode::StakingPaused);
+        require!(
+            cfg.unstake_cooldown_sec > 0,
+            ErrorCode::UnstakeCooldownDisabled
         );
+        check_stake_lock(cfg, &ctx.accounts.user_stake, now)?;
+        update_staking_global(cfg, now)?;
+        settle_staker_emission(cfg, now)?;
 
-        let pending_base = pending_stake(cfg, &ctx.accounts.user_stake)?;
-        let base_total = pending_base
-            .checked_add(ctx.accounts.user_stake.reward_owed as u128)
-            .ok_or(ErrorCode::MathOverflow)?;
-        require!(base_total > 0, ErrorCode::NothingToClaim);
+        let (burn_amount, transfer_amount) =
+            remove_stake(cfg, &mut ctx.accounts.user_stake, amount)?;
 
-        let bonus_bps = profile.badge_bonus_bps.min(BADGE_BONUS_CAP_BPS) as u128;
-        let payout = base_total
-            .checked_mul(BPS_DENOMINATOR + bonus_bps)
-            .ok_or(ErrorCode::MathOverflow)?
-            .checked_div(BPS_DENOMINATOR)
-            .ok_or(ErrorCode::MathOverflow)?;
-        let payout_u64 = u64::try_from(payout).map_err(|_| ErrorCode::MathOverflow)?;
+        if burn_amount > 0 {
+            let signer_seeds: &[&[u8]] = &[VAULT_SEED, &[cfg.bumps.vault_authority]];
+            token::burn(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    Burn {
+                        mint: ctx.accounts.mind_mint.to_account_info(),
+                        from: ctx.accounts.staking_mind_vault.to_account_info(),
+                        authority: ctx.accounts.vault_authority.to_account_info(),
+                    },
+                    &[signer_seeds],
+                ),
+                burn_amount,
+            )?;
+        }
 
-        let available = vault_available_lamports(&ctx.accounts.staking_reward_vault)?;
-        require!(available >= payout_u64, ErrorCode::InsufficientVaultBalance);
+        // A new request restarts the cooldown for everything still pending.
+        let cooldown =
+            i64::try_from(cfg.unstake_cooldown_sec).map_err(|_| ErrorCode::StakingMathOverflow)?;
+        let user_stake = &mut ctx.accounts.user_stake;
+        user_stake.pending_unstake = user_stake
+            .pending_unstake
+            .checked_add(transfer_amount)
+            .ok_or(ErrorCode::StakingMathOverflow)?;
+        user_stake.unstake_available_ts = now
+            .checked_add(cooldown)
+            .ok_or(ErrorCode::StakingMathOverflow)?;
+
+        emit!(UnstakeRequested {
+            owner: user_stake.owner,
+            amount,
+            pending_unstake: user_stake.pending_unstake,
+            unstake_available_ts: user_stake.unstake_available_ts,
+        });
+        Ok(())
+    }
 
-        transfer_lamports(
-            &ctx.accounts.staking_reward_vault.to_account_info(),
-            &ctx.accounts.owner.to_account_info(),
-            payout_u64,
-        )?;
+    pub fn complete_unstake(ctx: Context<CompleteUnstake>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let cfg = &ctx.accounts.config;
+        require!(!cfg.paused, ErrorCode::ProtocolPaused);
+        require!(!cfg.staking_paused, ErrorCode::StakingPaused);
+        let user_stake = &mut ctx.accounts.user_stake;
+        let amount = user_stake.pending_unstake;
+        require!(amount > 0, ErrorCode::NothingToClaim);
+        require!(
+            now >= user_stake.unstake_available_ts,
+            ErrorCode::UnstakeCooldownActive
+        );
 
-        ctx.accounts.user_stake.reward_owed = 0;
-        ctx.accounts.user_stake.reward_debt = earned_per_stake(
-            ctx.accounts.user_stake.staked_mind,
-            cfg.staking_acc_xnt_per_mind,
+        let signer_seeds: &[&[u8]] = &[VAULT_SEED, &[cfg.bumps.vault_authority]];
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.staking_mind_vault.to_account_info(),
+                    to: ctx.accounts.owner_mind_ata.to_account_info(),
+                    authority: ctx.accounts.vault_authority.to_account_info(),
+                },
+                &[signer_seeds],
+            ),
+            amount,
         )?;
+        user_stake.pending_unstake = 0;
 
-        cfg.staking_accounted_balance = cfg
-            .staking_accounted_balance
-            .checked_sub(payout_u64)
-            .ok_or(ErrorCode::MathOverflow)?;
-
-        emit!(XntClaimed {
+        emit!(MindUnstaked {
             owner: ctx.accounts.owner.key(),
-            amount: payout_u64,
-            bonus_bps: bonus_bps as u16,
+            amount,
         });
         Ok(())
     }
 
-    pub fn roll_epoch(ctx: Context<RollEpoch>, epoch_seconds: u64) -> Result<()> {
-        require!(epoch_seconds > 0, ErrorCode::InvalidAmount);
+    pub fn claim_staked_mind(ctx: Context<ClaimStakedMind>) -> Result<()> {
         let now = Clock::get()?.unix_timestamp;
         let cfg = &mut ctx.accounts.config;
-        require_keys_eq!(cfg.admin, ctx.accounts.admin.key(), ErrorCode::Unauthorized);
-        update_staking_global(cfg, now)?;
+        require!(!cfg.paused, ErrorCode::ProtocolPaused);
+        require!(!cfg.staking_paused, ErrorCode::StakingPaused);
+        settle_staker_emission(cfg, now)?;
+
+        let user_stake = &mut ctx.accounts.user_stake;
+        accrue_staked_mind_owed(cfg, user_stake)?;
+        user_stake.mind_reward_debt =
+            earned_per_stake(user_stake.staked_mind, cfg.staking_acc_mind_per_mind)?;
+        let reward = user_stake.mind_reward_owed;
+        require!(reward > 0, ErrorCode::NothingToClaim);
+        user_stake.mind_reward_owed = 0;
 
-        let vault_balance = vault_available_lamports(&ctx.accounts.staking_reward_vault)?;
-        if vault_balance > cfg.staking_accounted_balance {
-            let delta = vault_balance
-                .checked_sub(cfg.staking_accounted_balance)
-                .ok_or(ErrorCode::MathOverflow)?;
-            cfg.staking_undistributed_xnt = cfg
-                .staking_undistributed_xnt
-                .checked_add(delta)
-                .ok_or(ErrorCode::MathOverflow)?;
-            cfg.staking_accounted_balance = vault_balance;
-        }
+        let signer_seeds: &[&[u8]] = &[VAULT_SEED, &[cfg.bumps.vault_authority]];
+        token::mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                MintTo {
+                    mint: ctx.accounts.mind_mint.to_account_info(),
+                    to: ctx.accounts.owner_mind_ata.to_account_info(),
+                    authority: ctx.accounts.vault_authority.to_account_info(),
+                },
+                &[signer_seeds],
+            ),
+            reward,
+        )?;
 
-        if cfg.staking_total_staked_mind == 0 || cfg.staking_undistributed_xnt == 0 {
-            cfg.staking_reward_rate_xnt_per_sec = 0;
-            cfg.staking_epoch_end_ts = now;
-            cfg.staking_last_update_ts = now;
-            return Ok(());
-        }
+        emit!(StakedMindClaimed {
+            owner: ctx.accounts.owner.key(),
+            amount: reward,
+        });
+        Ok(())
+    }
 
-        let rate = cfg
-            .staking_undistributed_xnt
-            .checked_div(epoch_seconds)
-            .ok_or(ErrorCode::MathOverflow)?;
-        if rate == 0 {
-            cfg.staking_reward_rate_xnt_per_sec = 0;
-            cfg.staking_epoch_end_ts = now;
-            cfg.staking_last_update_ts = now;
-            return Ok(());
-        }
-        let distributed = rate
-            .checked_mul(epoch_seconds)
-            .ok_or(ErrorCode::MathOverflow)?;
-        cfg.staking_undistributed_xnt = cfg
-            .staking_undistributed_xnt
-            .checked_sub(distributed)
-            .ok_or(ErrorCode::MathOverflow)?;
-        cfg.staking_reward_rate_xnt_per_sec = rate;
-        cfg.staking_epoch_end_ts = now
-            .checked_add(epoch_seconds as i64)
-            .ok_or(ErrorCode::MathOverflow)?;
-        cfg.staking_last_update_ts = now;
+    /// Grows a legacy `UserStake` to the current layout. Pre-split stakes start earning
+    /// staker MIND emission from the migration onward; stakes from before the lock existed
+    /// count as already unlocked.
+    pub fn migrate_user_stake(ctx: Context<MigrateUserStake>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let info = ctx.accounts.user_stake.to_account_info();
+        let new_size = 8 + UserStake::INIT_SPACE;
+        require!(info.owner == &crate::ID, ErrorCode::InvalidUserStake);
+        let cfg = &mut ctx.accounts.config;
+        let migrated = {
+            let data = info.try_borrow_data()?;
+            require!(
+                data.len() >= 8 && data[..8] == UserStake::DISCRIMINATOR,
+                ErrorCode::InvalidUserStake
+            );
+            if data.len() >= new_size {
+                return Ok(());
+            }
+            let mut slice: &[u8] = &data[8..];
+            let legacy = UserStakeV1Legacy::deserialize(&mut slice)
+                .map_err(|_| ErrorCode::InvalidUserStake)?;
+            settle_staker_emission(cfg, now)?;
+            UserStake {
+                owner: legacy.owner,
+                staked_mind: legacy.staked_mind,
+                reward_debt: legacy.reward_debt,
+                reward_owed: legacy.reward_owed,
+                bump: legacy.bump,
+                mind_reward_debt: earned_per_stake(
+                    legacy.staked_mind,
+                    cfg.staking_acc_mind_per_mind,
+                )?,
+                mind_reward_owed: 0,
+                stake_start_ts: 0,
+                pending_unstake: 0,
+                unstake_available_ts: 0,
+            }
+        };
+        require_keys_eq!(
+            migrated.owner,
+            ctx.accounts.owner.key(),
+            ErrorCode::Unauthorized
+        );
 
-        emit!(EpochRolled {
-            rate,
-            epoch_end_ts: cfg.staking_epoch_end_ts,
+        resize_program_account(
+            &info,
+            &ctx.accounts.owner.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            new_size,
+        )?;
+        let mut data = info.try_borrow_mut_data()?;
+        let mut cursor: &mut [u8] = &mut data;
+        migrated.try_serialize(&mut cursor)?;
+        Ok(())
+    }
+
+    pub fn claim_xnt<'info>(ctx: Context<'_, '_, '_, 'info, ClaimXnt<'info>>) -> Result<()> {
+        let bump = *ctx.bumps.get("user_profile").unwrap();
+        claim_xnt_payout(ctx.accounts, ctx.remaining_accounts, bump, false)
+    }
+
+    pub fn claim_xnt_partial<'info>(
+        ctx: Context<'_, '_, '_, 'info, ClaimXnt<'info>>,
+    ) -> Result<()> {
+        let bump = *ctx.bumps.get("user_profile").unwrap();
+        claim_xnt_payout(ctx.accounts, ctx.remaining_accounts, bump, true)
+    }
+
+    pub fn roll_epoch(ctx: Context<RollEpoch>, epoch_seconds: u64) -> Result<()> {
+        require!(epoch_seconds > 0, ErrorCode::InvalidAmount);
+        let now = Clock::get()?.unix_timestamp;
+        let cfg = &mut ctx.accounts.config;
+        require!(!cfg.staking_paused, ErrorCode::StakingPaused);
+        let caller = ctx.accounts.admin.key();
+        require!(
+            caller == cfg.admin || is_epoch_roller(cfg, &caller),
+            ErrorCode::Unauthorized
+        );
+        update_staking_global(cfg, now)?;
+
+        let vault_balance = if xnt_is_native(cfg) {
+            vault_available_lamports(&ctx.accounts.staking_reward_vault)?
+        } else {
+            staking_xnt_vault_amount(cfg, ctx.remaining_accounts.first())?
+        };
+        sync_staking_vault_balance(cfg, vault_balance)?;
+        roll_staking_epoch(cfg, vault_balance, now, epoch_seconds)
+    }
+
+    pub fn admin_migrate_config(ctx: Context<AdminMigrateConfig>) -> Result<()> {
+        let info = ctx.accounts.config.to_account_info();
+        let new_size = 8 + Config::INIT_SPACE;
+        require!(info.owner == &crate::ID, ErrorCode::InvalidConfigAccount);
+        let legacy = {
+            let data = info.try_borrow_data()?;
+            require!(
+                data.len() >= 8 && data[..8] == Config::DISCRIMINATOR,
+                ErrorCode::InvalidConfigAccount
+            );
+            if data.len() >= new_size {
+                return Ok(());
+            }
+            let mut slice: &[u8] = &data[8..];
+            ConfigV1Legacy::deserialize(&mut slice).map_err(|_| ErrorCode::InvalidConfigAccount)?
+        };
+        require_keys_eq!(
+            legacy.admin,
+            ctx.accounts.admin.key(),
+            ErrorCode::Unauthorized
+        );
+
+        let migrated = Config {
+            admin: legacy.admin,
+            emission_per_sec: legacy.emission_per_sec,
+            acc_mind_per_hp: legacy.acc_mind_per_hp,
+            last_update_ts: legacy.last_update_ts,
+            network_hp_active: legacy.network_hp_active,
+            mind_mint: legacy.mind_mint,
+            xnt_mint: legacy.xnt_mint,
+            staking_reward_vault: legacy.staking_reward_vault,
+            treasury_vault: legacy.treasury_vault,
+            staking_mind_vault: legacy.staking_mind_vault,
+            max_effective_hp: legacy.max_effective_hp,
+            seconds_per_day: legacy.seconds_per_day,
+            staking_acc_xnt_per_mind: legacy.staking_acc_xnt_per_mind,
+            staking_last_update_ts: legacy.staking_last_update_ts,
+            staking_reward_rate_xnt_per_sec: legacy.staking_reward_rate_xnt_per_sec,
+            staking_epoch_end_ts: legacy.staking_epoch_end_ts,
+            staking_total_staked_mind: legacy.staking_total_staked_mind,
+            staking_undistributed_xnt: legacy.staking_undistributed_xnt,
+            staking_accounted_balance: legacy.staking_accounted_balance,
+            bumps: legacy.bumps,
+            auto_roll_epoch_seconds: 0,
+            forfeit_expired_rewards: false,
+            forfeit_window_seconds: 0,
+            unstake_cooldown_sec: 0,
+            treasury_sweep_threshold: 0,
+            treasury_destination: Pubkey::default(),
+            staking_xnt_vault: Pubkey::default(),
+            max_consecutive_renews: 0,
+            staker_emission_bps: 0,
+            staking_acc_mind_per_mind: 0,
+            epoch_rollers: [Pubkey::default(); MAX_EPOCH_ROLLERS],
+            max_mint_per_claim: u64::MAX,
+            staking_paused: false,
+            reward_round_half_up: false,
+            paused: false,
+            epoch_seq: 0,
+            staking_lock_days: 0,
+            badge_merkle_root: [0; 32],
+            crank_reward: 0,
+            min_position_hp: 0,
+            staking_share_bps: STAKING_SHARE_BPS,
+            level_bonus_table_initialized: false,
+        };
+
+        resize_program_account(
+            &info,
+            &ctx.accounts.admin.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            new_size,
+        )?;
+        let mut data = info.try_borrow_mut_data()?;
+        let mut cursor: &mut [u8] = &mut data;
+        migrated.try_serialize(&mut cursor)?;
+
+        emit!(ConfigMigrated {
+            admin: migrated.admin,
+            new_size: new_size as u64,
+        });
+        Ok(())
+    }
+
+    pub fn admin_set_auto_roll_epoch(
+        ctx: Context<AdminUpdateConfig>,
+        epoch_seconds: u64,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let cfg = &mut ctx.accounts.config;
+        require_keys_eq!(cfg.admin, ctx.accounts.admin.key(), ErrorCode::Unauthorized);
+        update_staking_global(cfg, now)?;
+        // 0 disables auto-roll and keeps `roll_epoch` as the only way to start an epoch.
+        cfg.auto_roll_epoch_seconds = epoch_seconds;
+        Ok(())
+    }
+
+    pub fn admin_set_forfeit_expired_rewards(
+        ctx: Context<AdminUpdateConfig>,
+        enabled: bool,
+        window_seconds: u64,
+    ) -> Result<()> {
+        let cfg = &mut ctx.accounts.config;
+        require_keys_eq!(cfg.admin, ctx.accounts.admin.key(), ErrorCode::Unauthorized);
+        require!(!enabled || window_seconds > 0, ErrorCode::InvalidAmount);
+        require!(
+            i64::try_from(window_seconds).is_ok(),
+            ErrorCode::InvalidAmount
+        );
+        cfg.forfeit_expired_rewards = enabled;
+        cfg.forfeit_window_seconds = window_seconds;
+        Ok(())
+    }
+
+    pub fn admin_set_unstake_cooldown(
+        ctx: Context<AdminUpdateConfig>,
+        cooldown_sec: u64,
+    ) -> Result<()> {
+        let cfg = &mut ctx.accounts.config;
+        require_keys_eq!(cfg.admin, ctx.accounts.admin.key(), ErrorCode::Unauthorized);
+        // 0 keeps the single-step `unstake_mind`; anything else requires request + complete.
+        cfg.unstake_cooldown_sec = cooldown_sec;
+        Ok(())
+    }
+
+    pub fn admin_set_epoch_rollers(
+        ctx: Context<AdminUpdateConfig>,
+        rollers: Vec<Pubkey>,
+    ) -> Result<()> {
+        let cfg = &mut ctx.accounts.config;
+        require_keys_eq!(cfg.admin, ctx.accounts.admin.key(), ErrorCode::Unauthorized);
+        require!(
+            rollers.len() <= MAX_EPOCH_ROLLERS,
+            ErrorCode::TooManyEpochRollers
+        );
+        // An empty list leaves `roll_epoch` to the admin alone.
+        let mut slots = [Pubkey::default(); MAX_EPOCH_ROLLERS];
+        slots[..rollers.len()].copy_from_slice(&rollers);
+        cfg.epoch_rollers = slots;
+        Ok(())
+    }
+
+    pub fn admin_set_staker_emission_bps(
+        ctx: Context<AdminUpdateConfig>,
+        staker_emission_bps: u16,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let cfg = &mut ctx.accounts.config;
+        require_keys_eq!(cfg.admin, ctx.accounts.admin.key(), ErrorCode::Unauthorized);
+        require!(
+            staker_emission_bps as u128 <= BPS_DENOMINATOR,
+            ErrorCode::InvalidStakerEmissionBps
+        );
+        // Settle the elapsed window under the old split before changing it.
+        update_mining_global(cfg, now)?;
+        cfg.staker_emission_bps = staker_emission_bps;
+        Ok(())
+    }
+
+    pub fn admin_pause(ctx: Context<AdminUpdateConfig>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let cfg = &mut ctx.accounts.config;
+        require_keys_eq!(cfg.admin, ctx.accounts.admin.key(), ErrorCode::Unauthorized);
+        update_mining_global(cfg, now)?;
+        cfg.paused = true;
+        emit!(ProtocolPauseChanged {
+            paused: true,
+            ts: now
+        });
+        Ok(())
+    }
+
+    pub fn admin_unpause(ctx: Context<AdminUpdateConfig>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let cfg = &mut ctx.accounts.config;
+        require_keys_eq!(cfg.admin, ctx.accounts.admin.key(), ErrorCode::Unauthorized);
+        cfg.paused = false;
+        // Emission restarts from now; the paused window is never back-filled.
+        cfg.last_update_ts = now;
+        emit!(ProtocolPauseChanged {
+            paused: false,
+            ts: now
         });
         Ok(())
     }
 
+    pub fn admin_set_staking_paused(ctx: Context<AdminUpdateConfig>, paused: bool) -> Result<()> {
+        let cfg = &mut ctx.accounts.config;
+        require_keys_eq!(cfg.admin, ctx.accounts.admin.key(), ErrorCode::Unauthorized);
+        // Freezes stake, unstake, XNT/MIND staking claims and epoch rolls; mining is unaffected.
+        cfg.staking_paused = paused;
+        Ok(())
+    }
+
+    /// Rounds claim-time earnings half-up instead of down. Claims settle `reward_debt`
+    /// with the same rounding, so over a position's life the difference stays within one
+    /// base unit per settlement; it can mint slightly more MIND than round-down.
+    pub fn admin_set_reward_rounding(
+        ctx: Context<AdminUpdateConfig>,
+        round_half_up: bool,
+    ) -> Result<()> {
+        let cfg = &mut ctx.accounts.config;
+        require_keys_eq!(cfg.admin, ctx.accounts.admin.key(), ErrorCode::Unauthorized);
+        cfg.reward_round_half_up = round_half_up;
+        Ok(())
+    }
+
+    pub fn admin_set_max_mint_per_claim(
+        ctx: Context<AdminUpdateConfig>,
+        max_mint_per_claim: u64,
+    ) -> Result<()> {
+        let cfg = &mut ctx.accounts.config;
+        require_keys_eq!(cfg.admin, ctx.accounts.admin.key(), ErrorCode::Unauthorized);
+        require!(max_mint_per_claim > 0, ErrorCode::InvalidAmount);
+        cfg.max_mint_per_claim = max_mint_per_claim;
+        Ok(())
+    }
+
+    pub fn admin_set_max_consecutive_renews(
+        ctx: Context<AdminUpdateConfig>,
+        max_renews: u16,
+    ) -> Result<()> {
+        let cfg = &mut ctx.accounts.config;
+        require_keys_eq!(cfg.admin, ctx.accounts.admin.key(), ErrorCode::Unauthorized);
+        // 0 keeps grace-period renewals unlimited.
+        cfg.max_consecutive_renews = max_renews;
+        Ok(())
+    }
+
     pub fn admin_update_config(
         ctx: Context<AdminUpdateConfig>,
         emission_per_sec: u64,
@@ -1326,6 +1973,59 @@ pub mod mining_v2 {
         Ok(())
     }
 
+    /// Takes the same 82_000..=90_000 range as `admin_update_config`; only `localnet` builds
+    /// accept any non-zero value, for short test days. Existing positions keep the `end_ts` they
+    /// were bought with; only new buys and renewals, grace and renew windows, and
+    /// duration-derived rig types of v1 positions use it.
+    pub fn admin_set_seconds_per_day(
+        ctx: Context<AdminUpdateConfig>,
+        seconds_per_day: u64,
+    ) -> Result<()> {
+        require!(
+            seconds_per_day_allowed(seconds_per_day),
+            ErrorCode::SecondsPerDayOutOfRange
+        );
+        let cfg = &mut ctx.accounts.config;
+        require_keys_eq!(cfg.admin, ctx.accounts.admin.key(), ErrorCode::Unauthorized);
+        let now = Clock::get()?.unix_timestamp;
+        update_mining_global(cfg, now)?;
+        cfg.seconds_per_day = seconds_per_day;
+        Ok(())
+    }
+
+    /// Zero disables the lock; otherwise stake can only be withdrawn this many days after
+    /// `stake_start_ts`.
+    pub fn admin_set_staking_lock_days(
+        ctx: Context<AdminUpdateConfig>,
+        staking_lock_days: u64,
+    ) -> Result<()> {
+        let cfg = &mut ctx.accounts.config;
+        require_keys_eq!(cfg.admin, ctx.accounts.admin.key(), ErrorCode::Unauthorized);
+        cfg.staking_lock_days = staking_lock_days;
+        Ok(())
+    }
+
+    /// Share of contract revenue routed to the staking reward vault; the rest goes to treasury.
+    pub fn admin_set_staking_share_bps(
+        ctx: Context<AdminUpdateConfig>,
+        staking_share_bps: u16,
+    ) -> Result<()> {
+        require!(
+            staking_share_bps as u128 <= BPS_DENOMINATOR,
+            ErrorCode::InvalidStakingShareBps
+        );
+        let cfg = &mut ctx.accounts.config;
+        require_keys_eq!(cfg.admin, ctx.accounts.admin.key(), ErrorCode::Unauthorized);
+        if cfg.staking_share_bps != staking_share_bps {
+            emit!(StakingShareUpdated {
+                old_bps: cfg.staking_share_bps,
+                new_bps: staking_share_bps,
+            });
+            cfg.staking_share_bps = staking_share_bps;
+        }
+        Ok(())
+    }
+
     pub fn admin_fix_accumulator(
         ctx: Context<AdminFixAccumulator>,
         new_acc_mind_per_hp: u128,
@@ -1334,6 +2034,14 @@ pub mod mining_v2 {
         let now = Clock::get()?.unix_timestamp;
         let cfg = &mut ctx.accounts.config;
         require_keys_eq!(cfg.admin, ctx.accounts.admin.key(), ErrorCode::Unauthorized);
+        let profile = load_user_profile_any(&ctx.accounts.user_profile)?;
+        let (expected_profile, _) =
+            Pubkey::find_program_address(&[PROFILE_SEED, profile.owner.as_ref()], ctx.program_id);
+        require_keys_eq!(
+            ctx.accounts.user_profile.key(),
+            expected_profile,
+            ErrorCode::InvalidUserProfileOwner
+        );
 
         cfg.acc_mind_per_hp = new_acc_mind_per_hp;
         cfg.last_update_ts = now;
@@ -1341,16 +2049,125 @@ pub mod mining_v2 {
         for info in ctx.remaining_accounts.iter() {
             require!(info.is_writable, ErrorCode::InvalidPositionSize);
             let mut position = load_position_any(info)?;
-            require_keys_eq!(position.owner, ctx.accounts.user_profile.owner, ErrorCode::InvalidPositionOwner);
+            require_keys_eq!(
+                position.owner,
+                profile.owner,
+                ErrorCode::InvalidPositionOwner
+            );
             let rig_type = position_rig_type(&position, cfg)?;
             let base_hp_scaled = position_base_hp_scaled(&position)?;
             let buff_bps = position_buff_bps(&position, rig_type, now);
-            let hp_effective = effective_hp_scaled(base_hp_scaled, ctx.accounts.user_profile.level, buff_bps)?;
+            let hp_effective =
+                effective_hp_with_bonus(base_hp_scaled, buff_bps, profile.level_bonus_bps)?;
             position.reward_debt = earned_per_hp(hp_effective, new_acc_mind_per_hp)?;
-            position.last_level_applied = ctx.accounts.user_profile.level;
+            position.last_level_applied = profile.level;
+            position.level_bonus_bps = profile.level_bonus_bps;
+            save_position(info, &position)?;
+        }
+
+        Ok(())
+    }
+
+    /// Positions that expired before `admin_enable_hp_scaling` froze an unscaled
+    /// `final_acc_mind_per_hp`; rescaling keeps `hp * acc` (and `reward_debt`) unchanged.
+    /// Reads already apply the same conversion, so this only persists it.
+    pub fn admin_rescale_legacy_positions<'info>(
+        ctx: Context<'_, '_, '_, 'info, AdminRescaleLegacyPositions<'info>>,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.hp_scale_config.enabled,
+            ErrorCode::HpScaleNotEnabled
+        );
+        let admin = ctx.accounts.admin.to_account_info();
+        let system_program = ctx.accounts.system_program.to_account_info();
+
+        for info in ctx.remaining_accounts.iter() {
+            require!(info.is_writable, ErrorCode::InvalidPositionSize);
+            let mut position = load_position_unscaled(info)?;
+            require!(
+                rescale_legacy_expired(&mut position)?,
+                ErrorCode::PositionNotRescalable
+            );
+            ensure_position_v2(info, &admin, &system_program)?;
             save_position(info, &position)?;
+
+            emit!(PositionRescaled {
+                owner: position.owner,
+                position: info.key(),
+                hp_scaled: position.hp,
+                final_acc_mind_per_hp: position.final_acc_mind_per_hp,
+            });
+        }
+        Ok(())
+    }
+
+    /// Break-glass fix for positions left inconsistent by legacy layouts. Only the listed fields
+    /// change; network and profile HP are not adjusted, so flipping `expired`/`deactivated` must be
+    /// paired with `admin_set_network_hp_active` when the position's HP was still counted.
+    pub fn admin_repair_position(
+        ctx: Context<AdminRepairPosition>,
+        params: RepairPositionParams,
+    ) -> Result<()> {
+        let cfg = &ctx.accounts.config;
+        let old = load_position_any(&ctx.accounts.position)?;
+        let mut position = old.clone();
+
+        if let Some(reward_debt) = params.reward_debt {
+            position.reward_debt = reward_debt;
+        }
+        if let Some(final_acc) = params.final_acc_mind_per_hp {
+            position.final_acc_mind_per_hp = final_acc;
+        }
+        if let Some(expired) = params.expired {
+            position.expired = expired;
+        }
+        if let Some(deactivated) = params.deactivated {
+            position.deactivated = deactivated;
+        }
+        if let Some(buff_level) = params.buff_level {
+            let rig_type = position_rig_type(&position, cfg)?;
+            require!(
+                buff_level <= rig_max_buff_level(rig_type),
+                ErrorCode::InvalidRepairParams
+            );
+            position.buff_level = buff_level;
+        }
+        if let Some(last_level_applied) = params.last_level_applied {
+            position.last_level_applied = last_level_applied;
         }
+        require!(
+            !position.deactivated || position.expired,
+            ErrorCode::InvalidRepairParams
+        );
+        require!(
+            !position.expired || position.final_acc_mind_per_hp <= cfg.acc_mind_per_hp,
+            ErrorCode::InvalidRepairParams
+        );
+
+        ensure_position_v2(
+            &ctx.accounts.position,
+            &ctx.accounts.admin.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+        save_position(&ctx.accounts.position, &position)?;
 
+        emit!(PositionRepaired {
+            admin: ctx.accounts.admin.key(),
+            position: ctx.accounts.position.key(),
+            owner: position.owner,
+            old_reward_debt: old.reward_debt,
+            new_reward_debt: position.reward_debt,
+            old_final_acc_mind_per_hp: old.final_acc_mind_per_hp,
+            new_final_acc_mind_per_hp: position.final_acc_mind_per_hp,
+            old_expired: old.expired,
+            new_expired: position.expired,
+            old_deactivated: old.deactivated,
+            new_deactivated: position.deactivated,
+            old_buff_level: old.buff_level,
+            new_buff_level: position.buff_level,
+            old_last_level_applied: old.last_level_applied,
+            new_last_level_applied: position.last_level_applied,
+        });
         Ok(())
     }
 
@@ -1396,6 +2213,7 @@ pub mod mining_v2 {
         require_keys_eq!(cfg.admin, ctx.accounts.admin.key(), ErrorCode::Unauthorized);
 
         cfg.xnt_mint = System::id();
+        cfg.staking_xnt_vault = Pubkey::default();
         cfg.staking_reward_vault = ctx.accounts.staking_reward_vault.key();
         cfg.treasury_vault = ctx.accounts.treasury_vault.key();
 
@@ -1412,6 +2230,24 @@ pub mod mining_v2 {
         Ok(())
     }
 
+    pub fn admin_use_spl_xnt(ctx: Context<AdminUseSplXnt>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let cfg = &mut ctx.accounts.config;
+        require_keys_eq!(cfg.admin, ctx.accounts.admin.key(), ErrorCode::Unauthorized);
+        update_staking_global(cfg, now)?;
+
+        cfg.xnt_mint = ctx.accounts.xnt_mint.key();
+        cfg.staking_xnt_vault = ctx.accounts.staking_xnt_vault.key();
+
+        // Reset staking epoch accounting to align with the SPL reward vault.
+        cfg.staking_accounted_balance = ctx.accounts.staking_xnt_vault.amount;
+        cfg.staking_undistributed_xnt = 0;
+        cfg.staking_reward_rate_xnt_per_sec = 0;
+        cfg.staking_epoch_end_ts = now;
+        cfg.staking_last_update_ts = now;
+        Ok(())
+    }
+
     pub fn admin_set_metadata(
         ctx: Context<AdminSetMetadata>,
         params: MetadataParams,
@@ -1530,6 +2366,13 @@ pub mod mining_v2 {
             amount,
         )?;
 
+        if !xnt_is_native(cfg) {
+            emit!(StakingRewardsWithdrawn {
+                admin: cfg.admin,
+                amount,
+            });
+            return Ok(());
+        }
         let remaining = vault_available_lamports(&ctx.accounts.staking_reward_vault)?;
         cfg.staking_accounted_balance = remaining;
         cfg.staking_undistributed_xnt =
@@ -1561,10 +2404,52 @@ pub mod mining_v2 {
         Ok(())
     }
 
-    pub fn admin_set_badge(
-        ctx: Context<AdminSetBadge>,
-        badge_tier: u8,
-        badge_bonus_bps: u16,
+    pub fn admin_set_treasury_sweep(
+        ctx: Context<AdminUpdateConfig>,
+        threshold: u64,
+        destination: Pubkey,
+    ) -> Result<()> {
+        let cfg = &mut ctx.accounts.config;
+        require_keys_eq!(cfg.admin, ctx.accounts.admin.key(), ErrorCode::Unauthorized);
+        // The default pubkey disables `sweep_treasury`.
+        cfg.treasury_sweep_threshold = threshold;
+        cfg.treasury_destination = destination;
+        Ok(())
+    }
+
+    pub fn sweep_treasury(ctx: Context<SweepTreasury>) -> Result<()> {
+        let cfg = &ctx.accounts.config;
+        require!(
+            cfg.treasury_destination != Pubkey::default(),
+            ErrorCode::TreasurySweepDisabled
+        );
+        let available = vault_available_lamports(&ctx.accounts.treasury_vault)?;
+        require!(
+            available > cfg.treasury_sweep_threshold,
+            ErrorCode::TreasuryBelowThreshold
+        );
+        let amount = available
+            .checked_sub(cfg.treasury_sweep_threshold)
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        transfer_lamports(
+            &ctx.accounts.treasury_vault.to_account_info(),
+            &ctx.accounts.treasury_destination.to_account_info(),
+            amount,
+        )?;
+
+        emit!(TreasurySwept {
+            destination: cfg.treasury_destination,
+            amount,
+            remaining: cfg.treasury_sweep_threshold,
+        });
+        Ok(())
+    }
+
+    pub fn admin_set_badge(
+        ctx: Context<AdminSetBadge>,
+        badge_tier: u8,
+        badge_bonus_bps: u16,
     ) -> Result<()> {
         let cfg = &ctx.accounts.config;
         require_keys_eq!(cfg.admin, ctx.accounts.admin.key(), ErrorCode::Unauthorized);
@@ -1609,184 +2494,1480 @@ pub mod mining_v2 {
         save_user_profile(&ctx.accounts.user_profile, &profile)?;
         Ok(())
     }
+
+    pub fn get_user_summary(ctx: Context<GetUserSummary>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let cfg = &ctx.accounts.config;
+        let owner = ctx.accounts.owner.key();
+
+        let (level, xp, active_hp, badge_tier, badge_bonus_bps) =
+            if ctx.accounts.user_profile.data_is_empty() {
+                (0, 0, 0, 0, 0)
+            } else {
+                let mut profile = load_user_profile_any(&ctx.accounts.user_profile)?;
+                require_keys_eq!(profile.owner, owner, ErrorCode::Unauthorized);
+                ensure_profile_hp_scaled(&mut profile)?;
+                update_user_xp(&mut profile, now)?;
+                (
+                    profile.level,
+                    profile.xp,
+                    profile.active_hp,
+                    profile.badge_tier,
+                    profile.badge_bonus_bps.min(BADGE_BONUS_CAP_BPS),
+                )
+            };
+
+        let (staked_mind, pending_xnt) = if ctx.accounts.user_stake.data_is_empty() {
+            (0, 0)
+        } else {
+            let user_stake = load_user_stake(&ctx.accounts.user_stake)?;
+            require_keys_eq!(user_stake.owner, owner, ErrorCode::Unauthorized);
+            let acc = simulated_staking_acc(cfg, now)?;
+            let earned = earned_per_stake(user_stake.staked_mind, acc)?;
+            let base_total = earned
+                .saturating_sub(user_stake.reward_debt)
+                .checked_add(user_stake.reward_owed as u128)
+                .ok_or(ErrorCode::MathOverflow)?;
+            let payout = base_total
+                .checked_mul(BPS_DENOMINATOR + badge_bonus_bps as u128)
+                .ok_or(ErrorCode::MathOverflow)?
+                .checked_div(BPS_DENOMINATOR)
+                .ok_or(ErrorCode::MathOverflow)?;
+            (
+                user_stake.staked_mind,
+                u64::try_from(payout).map_err(|_| ErrorCode::MathOverflow)?,
+            )
+        };
+
+        emit!(UserSummary {
+            owner,
+            level,
+            xp,
+            active_hp,
+            badge_tier,
+            badge_bonus_bps,
+            staked_mind,
+            pending_xnt,
+        });
+        Ok(())
+    }
+
+    pub fn get_vault_balances(ctx: Context<GetVaultBalances>) -> Result<()> {
+        let cfg = &ctx.accounts.config;
+        emit!(VaultBalances {
+            treasury_lamports: vault_available_lamports(&ctx.accounts.treasury_vault)?,
+            staking_reward_lamports: vault_available_lamports(&ctx.accounts.staking_reward_vault)?,
+            staking_accounted_balance: cfg.staking_accounted_balance,
+            staking_undistributed_xnt: cfg.staking_undistributed_xnt,
+        });
+        Ok(())
+    }
+
+    /// Reports the index the next `buy_contract` must use, so clients derive the position PDA fresh.
+    pub fn reserve_position_index(ctx: Context<ReservePositionIndex>) -> Result<()> {
+        let owner = ctx.accounts.owner.key();
+        let next_position_index = if ctx.accounts.user_profile.data_is_empty() {
+            0
+        } else {
+            let profile = load_user_profile_any(&ctx.accounts.user_profile)?;
+            require_keys_eq!(profile.owner, owner, ErrorCode::Unauthorized);
+            profile.next_position_index
+        };
+        let (position, _) = Pubkey::find_program_address(
+            &[
+                POSITION_SEED,
+                owner.as_ref(),
+                &next_position_index.to_le_bytes(),
+            ],
+            ctx.program_id,
+        );
+
+        emit!(NextPositionIndex {
+            owner,
+            next_position_index,
+            position,
+        });
+        Ok(())
+    }
+
+    pub fn get_contract_terms(_ctx: Context<GetContractTerms>, contract_type: u8) -> Result<()> {
+        let (duration_days, base_hp_scaled, cost_base) = contract_terms(contract_type)?;
+        emit!(ContractTerms {
+            contract_type,
+            duration_days,
+            base_hp_scaled,
+            cost_base,
+        });
+        Ok(())
+    }
+
+    pub fn quote_buy_contract(ctx: Context<QuoteBuyContract>, contract_type: u8) -> Result<()> {
+        let bonus_table = load_level_bonus_table(
+            ctx.accounts.level_bonus_table.as_deref(),
+            &ctx.accounts.config,
+        )?;
+        let now = Clock::get()?.unix_timestamp;
+        let cfg = &ctx.accounts.config;
+        let owner = ctx.accounts.owner.key();
+        let (duration_days, base_hp_scaled, cost_base) = contract_terms(contract_type)?;
+        let (staking_share, treasury_share) =
+            split_contract_cost(cost_base, cfg.staking_share_bps)?;
+
+        let level = if ctx.accounts.user_profile.data_is_empty() {
+            1
+        } else {
+            let mut profile = load_user_profile_any(&ctx.accounts.user_profile)?;
+            require_keys_eq!(profile.owner, owner, ErrorCode::Unauthorized);
+            update_user_xp(&mut profile, now)?;
+            profile.level
+        };
+        let hp_effective =
+            effective_hp_scaled(base_hp_scaled as u128, level, 0, bonus_table.as_ref())?;
+        let network_after = (cfg.network_hp_active as u128)
+            .checked_add(hp_effective)
+            .ok_or(ErrorCode::MathOverflow)?;
+        let network_share_bps = hp_effective
+            .checked_mul(BPS_DENOMINATOR)
+            .ok_or(ErrorCode::MathOverflow)?
+            .checked_div(network_after)
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        emit!(BuyQuote {
+            owner,
+            contract_type,
+            duration_days,
+            base_hp_scaled,
+            cost_base,
+            staking_share,
+            treasury_share,
+            hp_effective: u64::try_from(hp_effective).map_err(|_| ErrorCode::MathOverflow)?,
+            network_share_bps: u16::try_from(network_share_bps)
+                .map_err(|_| ErrorCode::MathOverflow)?,
+        });
+        Ok(())
+    }
+
+    /// Positions come in `remaining_accounts`; one `FleetBuffQuote` is emitted per active position
+    /// that can still be buffed. The fleet figure is the base-HP-weighted buff after that upgrade.
+    pub fn get_fleet_buff_quotes(ctx: Context<GetFleetBuffQuotes>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let cfg = &ctx.accounts.config;
+        let owner = ctx.accounts.owner.key();
+
+        let mut fleet = Vec::with_capacity(ctx.remaining_accounts.len());
+        let mut fleet_base_hp: u128 = 0;
+        let mut fleet_buffed_weight: u128 = 0;
+        for info in ctx.remaining_accounts.iter() {
+            let position = load_position_any(info)?;
+            require_keys_eq!(position.owner, owner, ErrorCode::InvalidPositionOwner);
+            if position.deactivated || position.expired {
+                continue;
+            }
+            let rig_type = position_rig_type(&position, cfg)?;
+            let base_hp_scaled = position_base_hp_scaled(&position)?;
+            let buff_bps = position_buff_bps(&position, rig_type, now);
+            fleet_base_hp = fleet_base_hp
+                .checked_add(base_hp_scaled)
+                .ok_or(ErrorCode::MathOverflow)?;
+            fleet_buffed_weight = fleet_buffed_weight
+                .checked_add(
+                    base_hp_scaled
+                        .checked_mul(buff_bps as u128)
+                        .ok_or(ErrorCode::MathOverflow)?,
+                )
+                .ok_or(ErrorCode::MathOverflow)?;
+            fleet.push((info.key(), position, rig_type, base_hp_scaled, buff_bps));
+        }
+
+        for (key, position, rig_type, base_hp_scaled, buff_bps) in fleet {
+            if position.buff_level >= rig_max_buff_level(rig_type) {
+                continue;
+            }
+            let next_buff_level = position
+                .buff_level
+                .checked_add(1)
+                .ok_or(ErrorCode::MathOverflow)?;
+            let next_buff_bps = rig_buff_bps(rig_type, next_buff_level);
+            let current_weight = base_hp_scaled
+                .checked_mul(buff_bps as u128)
+                .ok_or(ErrorCode::MathOverflow)?;
+            let weight_after = fleet_buffed_weight
+                .checked_sub(current_weight)
+                .ok_or(ErrorCode::MathOverflow)?
+                .checked_add(
+                    base_hp_scaled
+                        .checked_mul(next_buff_bps as u128)
+                        .ok_or(ErrorCode::MathOverflow)?,
+                )
+                .ok_or(ErrorCode::MathOverflow)?;
+            let fleet_buff_bps_after = weight_after
+                .checked_div(fleet_base_hp)
+                .ok_or(ErrorCode::MathOverflow)?;
+            let fleet_buff_bps_after =
+                u16::try_from(fleet_buff_bps_after).map_err(|_| ErrorCode::MathOverflow)?;
+            let (_, _, renew_cost_base) = contract_terms(rig_type)?;
+
+            emit!(FleetBuffQuote {
+                owner,
+                position: key,
+                rig_type,
+                buff_level: position.buff_level,
+                next_buff_level,
+                current_buff_bps: buff_bps,
+                next_buff_bps,
+                renew_cost_base,
+                fleet_buff_bps_after,
+                exceeds_cap: fleet_buff_bps_after > RIG_BUFF_CAP_BPS,
+            });
+        }
+        Ok(())
+    }
+
+    /// Previews `claim_mind` over every position in `remaining_accounts` without minting.
+    /// Positions are settled in order against a scratch copy of config and profile, so
+    /// expiries earlier in the list affect later ones exactly as sequential claims would.
+    pub fn simulate_claim_all(ctx: Context<SimulateClaimAll>) -> Result<()> {
+        let bonus_table = load_level_bonus_table(
+            ctx.accounts.level_bonus_table.as_deref(),
+            &ctx.accounts.config,
+        )?;
+        let now = Clock::get()?.unix_timestamp;
+        let owner = ctx.accounts.owner.key();
+        let mut cfg: Config = (**ctx.accounts.config).clone();
+        let mut profile = load_user_profile_any(&ctx.accounts.user_profile)?;
+        require_keys_eq!(profile.owner, owner, ErrorCode::Unauthorized);
+        ensure_profile_hp_scaled(&mut profile)?;
+        update_user_xp(&mut profile, now)?;
+
+        let mut total_pending: u64 = 0;
+        for info in ctx.remaining_accounts.iter() {
+            let mut position = load_position_any(info)?;
+            require_keys_eq!(position.owner, owner, ErrorCode::InvalidPositionOwner);
+            let (pending, hp_effective) = simulate_position_claim(
+                &mut cfg,
+                &mut position,
+                &mut profile,
+                now,
+                bonus_table.as_ref(),
+            )?;
+            total_pending = total_pending
+                .checked_add(pending)
+                .ok_or(ErrorCode::MathOverflow)?;
+            emit!(SimulatedClaim {
+                owner,
+                position: info.key(),
+                pending,
+                expired: position.expired,
+                hp_effective,
+            });
+        }
+
+        emit!(SimulatedClaimTotal {
+            owner,
+            positions: ctx.remaining_accounts.len() as u32,
+            total_pending,
+        });
+        Ok(())
+    }
+
+    /// `remaining_accounts` must be the position PDAs for `start_index..end_index` in order;
+    /// bit `i` of `bitmap` is set when `start_index + i` exists, of `active_bitmap` when it is mining.
+    pub fn probe_positions(
+        ctx: Context<ProbePositions>,
+        start_index: u64,
+        end_index: u64,
+    ) -> Result<()> {
+        require!(end_index > start_index, ErrorCode::InvalidAmount);
+        let count = end_index
+            .checked_sub(start_index)
+            .ok_or(ErrorCode::MathOverflow)?;
+        require!(count <= 64, ErrorCode::InvalidAmount);
+        require!(
+            ctx.remaining_accounts.len() as u64 == count,
+            ErrorCode::InvalidAmount
+        );
+        let owner = ctx.accounts.owner.key();
+
+        let mut bitmap: u64 = 0;
+        let mut active_bitmap: u64 = 0;
+        for (offset, info) in ctx.remaining_accounts.iter().enumerate() {
+            let index = start_index
+                .checked_add(offset as u64)
+                .ok_or(ErrorCode::MathOverflow)?;
+            let (expected, _) = Pubkey::find_program_address(
+                &[POSITION_SEED, owner.as_ref(), &index.to_le_bytes()],
+                ctx.program_id,
+            );
+            require_keys_eq!(info.key(), expected, ErrorCode::InvalidPositionIndex);
+            if info.owner != &crate::ID || info.data_is_empty() {
+                continue;
+            }
+            bitmap |= 1 << offset;
+            let position = load_position_any(info)?;
+            if !position.deactivated && !position.expired {
+                active_bitmap |= 1 << offset;
+            }
+        }
+
+        emit!(PositionExistence {
+            owner,
+            start_index,
+            bitmap,
+            active_bitmap,
+        });
+        Ok(())
+    }
+
+    pub fn admin_propose_transfer(
+        ctx: Context<AdminProposeTransfer>,
+        new_admin: Pubkey,
+    ) -> Result<()> {
+        let cfg = &ctx.accounts.config;
+        require_keys_eq!(cfg.admin, ctx.accounts.admin.key(), ErrorCode::Unauthorized);
+        require!(
+            new_admin != Pubkey::default(),
+            ErrorCode::InvalidAdminCandidate
+        );
+        let pending = &mut ctx.accounts.pending_admin;
+        pending.candidate = new_admin;
+        pending.bump = *ctx.bumps.get("pending_admin").unwrap();
+        emit!(AdminTransferProposed {
+            admin: cfg.admin,
+            candidate: new_admin,
+        });
+        Ok(())
+    }
+
+    pub fn admin_accept_transfer(ctx: Context<AdminAcceptTransfer>) -> Result<()> {
+        require_keys_eq!(
+            ctx.accounts.pending_admin.candidate,
+            ctx.accounts.new_admin.key(),
+            ErrorCode::Unauthorized
+        );
+        let cfg = &mut ctx.accounts.config;
+        let old_admin = cfg.admin;
+        cfg.admin = ctx.accounts.new_admin.key();
+        emit!(AdminTransferred {
+            old_admin,
+            new_admin: cfg.admin,
+        });
+        Ok(())
+    }
+
+    pub fn admin_cancel_transfer(ctx: Context<AdminCancelTransfer>) -> Result<()> {
+        require_keys_eq!(
+            ctx.accounts.config.admin,
+            ctx.accounts.admin.key(),
+            ErrorCode::Unauthorized
+        );
+        Ok(())
+    }
+
+    /// Permissionless fallback for `roll_epoch` once the current epoch has ended, so
+    /// staking rewards keep flowing when no admin or roller is around. Uses a fixed
+    /// `CRANK_EPOCH_DAYS` epoch.
+    pub fn crank_epoch(ctx: Context<CrankEpoch>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let cfg = &mut ctx.accounts.config;
+        require!(!cfg.paused, ErrorCode::ProtocolPaused);
+        require!(!cfg.staking_paused, ErrorCode::StakingPaused);
+        require!(now > cfg.staking_epoch_end_ts, ErrorCode::EpochNotEnded);
+        update_staking_global(cfg, now)?;
+        // With `auto_roll_epoch_seconds` set the update above may already have started the next
+        // epoch; rolling again would overwrite its committed rate with the leftover remainder.
+        if now <= cfg.staking_epoch_end_ts {
+            return Ok(());
+        }
+
+        let vault_balance = if xnt_is_native(cfg) {
+            vault_available_lamports(&ctx.accounts.staking_reward_vault)?
+        } else {
+            staking_xnt_vault_amount(cfg, ctx.remaining_accounts.first())?
+        };
+        sync_staking_vault_balance(cfg, vault_balance)?;
+        require!(
+            cfg.staking_undistributed_xnt > 0,
+            ErrorCode::NothingToDistribute
+        );
+        let epoch_seconds = cfg
+            .seconds_per_day
+            .checked_mul(CRANK_EPOCH_DAYS)
+            .ok_or(ErrorCode::StakingMathOverflow)?;
+        roll_staking_epoch(cfg, vault_balance, now, epoch_seconds)
+    }
+
+    pub fn admin_init_level_bonus_table(
+        ctx: Context<AdminInitLevelBonusTable>,
+        bonuses: [u16; LEVEL_BONUS_LEVELS],
+    ) -> Result<()> {
+        require_keys_eq!(
+            ctx.accounts.config.admin,
+            ctx.accounts.admin.key(),
+            ErrorCode::Unauthorized
+        );
+        validate_level_bonuses(&bonuses)?;
+        let table = &mut ctx.accounts.level_bonus_table;
+        table.bonuses = bonuses;
+        table.bump = *ctx.bumps.get("level_bonus_table").unwrap();
+        ctx.accounts.config.level_bonus_table_initialized = true;
+        emit!(LevelBonusTableUpdated {
+            admin: ctx.accounts.admin.key(),
+            bonuses,
+        });
+        Ok(())
+    }
+
+    /// Takes effect lazily: each profile, and its share of `network_hp_active`, moves to the
+    /// new bonuses when its owner next settles, and each position when it is next settled.
+    /// MIND accrued before that keeps the old bonus.
+    pub fn admin_update_level_bonus_table(
+        ctx: Context<AdminUpdateLevelBonusTable>,
+        bonuses: [u16; LEVEL_BONUS_LEVELS],
+    ) -> Result<()> {
+        require_keys_eq!(
+            ctx.accounts.config.admin,
+            ctx.accounts.admin.key(),
+            ErrorCode::Unauthorized
+        );
+        validate_level_bonuses(&bonuses)?;
+        ctx.accounts.level_bonus_table.bonuses = bonuses;
+        emit!(LevelBonusTableUpdated {
+            admin: ctx.accounts.admin.key(),
+            bonuses,
+        });
+        Ok(())
+    }
+
+    /// Returns the rent of a deactivated, fully claimed position to its owner.
+    pub fn close_position(ctx: Context<ClosePosition>, position_index: u64) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let owner = ctx.accounts.owner.key();
+        let info = ctx.accounts.position.to_account_info();
+        let position = load_position_any(&info)?;
+        require_keys_eq!(position.owner, owner, ErrorCode::Unauthorized);
+        require!(position.deactivated, ErrorCode::PositionNotDeactivated);
+        // Deactivated positions carry their own HP and accumulator, so level and bonus
+        // table do not matter here.
+        let (hp_effective, acc_used) =
+            effective_hp_for_claim(&position, 0, &ctx.accounts.config, now)?;
+        let pending = pending_mind(
+            hp_effective,
+            acc_used,
+            position.reward_debt,
+            ctx.accounts.config.reward_round_half_up,
+        )?;
+        require!(pending == 0, ErrorCode::UnclaimedRewards);
+        close_program_account(&info, &ctx.accounts.owner.to_account_info())?;
+        emit!(PositionClosed {
+            owner,
+            position_index,
+        });
+        Ok(())
+    }
+
+    /// Creates or updates the owner's grant to `delegate`. A delegate signing for the owner
+    /// pays any rent and XNT itself, stakes only MIND the owner approved to it, and can
+    /// only top up an existing stake.
+    pub fn set_delegate(
+        ctx: Context<SetDelegate>,
+        delegate: Pubkey,
+        permissions: u8,
+    ) -> Result<()> {
+        require!(
+            permissions != 0 && permissions & !DELEGATE_PERMISSION_ALL == 0,
+            ErrorCode::InvalidDelegatePermissions
+        );
+        require!(
+            delegate != ctx.accounts.owner.key() && delegate != Pubkey::default(),
+            ErrorCode::InvalidDelegate
+        );
+        let record = &mut ctx.accounts.delegate_record;
+        record.owner = ctx.accounts.owner.key();
+        record.delegate = delegate;
+        record.permissions = permissions;
+        record.bump = *ctx.bumps.get("delegate_record").unwrap();
+        emit!(DelegateSet {
+            owner: record.owner,
+            delegate,
+            permissions,
+        });
+        Ok(())
+    }
+
+    pub fn revoke_delegate(ctx: Context<RevokeDelegate>) -> Result<()> {
+        let record = &ctx.accounts.delegate_record;
+        let signer = ctx.accounts.authority.key();
+        require!(
+            signer == record.owner || signer == record.delegate,
+            ErrorCode::Unauthorized
+        );
+        emit!(DelegateRevoked {
+            owner: record.owner,
+            delegate: record.delegate,
+        });
+        Ok(())
+    }
+
+    /// All-zero root disables `claim_badge`.
+    pub fn admin_set_badge_merkle_root(
+        ctx: Context<AdminUpdateConfig>,
+        root: [u8; 32],
+    ) -> Result<()> {
+        let cfg = &mut ctx.accounts.config;
+        require_keys_eq!(cfg.admin, ctx.accounts.admin.key(), ErrorCode::Unauthorized);
+        cfg.badge_merkle_root = root;
+        Ok(())
+    }
+
+    /// Self-service `admin_set_badge`: the leaf is `sha256(owner || tier || bonus_bps_le)`
+    /// and pairs are hashed in sorted order.
+    pub fn claim_badge(
+        ctx: Context<ClaimBadge>,
+        badge_tier: u8,
+        badge_bonus_bps: u16,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        let cfg = &ctx.accounts.config;
+        require!(
+            cfg.badge_merkle_root != [0; 32],
+            ErrorCode::BadgeMerkleRootNotSet
+        );
+        require!(
+            proof.len() <= MAX_BADGE_PROOF_LEN,
+            ErrorCode::InvalidMerkleProof
+        );
+        let owner = ctx.accounts.owner.key();
+        let leaf = hashv(&[
+            owner.as_ref(),
+            &[badge_tier],
+            &badge_bonus_bps.to_le_bytes(),
+        ])
+        .to_bytes();
+        require!(
+            verify_merkle_proof(&proof, cfg.badge_merkle_root, leaf),
+            ErrorCode::InvalidMerkleProof
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        let bump = *ctx.bumps.get("user_profile").unwrap();
+        let mut profile = ensure_user_profile_v2(
+            &ctx.accounts.user_profile,
+            &ctx.accounts.owner.to_account_info(),
+            &ctx.accounts.system_program,
+            owner,
+            bump,
+            now,
+        )?;
+        require_keys_eq!(profile.owner, owner, ErrorCode::Unauthorized);
+        profile.badge_tier = badge_tier;
+        profile.badge_bonus_bps = badge_bonus_bps.min(BADGE_BONUS_CAP_BPS);
+        save_user_profile(&ctx.accounts.user_profile, &profile)?;
+        emit!(BadgeClaimed {
+            owner,
+            badge_tier,
+            badge_bonus_bps: profile.badge_bonus_bps,
+        });
+        Ok(())
+    }
+
+    /// Lamports paid from the treasury vault per position `crank_deactivate` deactivates.
+    pub fn admin_set_crank_reward(
+        ctx: Context<AdminUpdateConfig>,
+        crank_reward: u64,
+    ) -> Result<()> {
+        let cfg = &mut ctx.accounts.config;
+        require_keys_eq!(cfg.admin, ctx.accounts.admin.key(), ErrorCode::Unauthorized);
+        cfg.crank_reward = crank_reward;
+        Ok(())
+    }
+
+    /// Permissionless `deactivate_position` for owners who never call it, so their HP
+    /// stops counting toward `network_hp_active`. `remaining_accounts` holds
+    /// `(position, owner profile)` pairs; positions still inside their grace period or
+    /// already deactivated are skipped.
+    pub fn crank_deactivate<'info>(
+        ctx: Context<'_, '_, '_, 'info, CrankDeactivate<'info>>,
+    ) -> Result<()> {
+        let bonus_table = load_level_bonus_table(
+            ctx.accounts.level_bonus_table.as_deref(),
+            &ctx.accounts.config,
+        )?;
+        let accounts = ctx.remaining_accounts;
+        require!(
+            accounts.chunks_exact(2).remainder().is_empty(),
+            ErrorCode::InvalidCrankAccounts
+        );
+        require!(
+            accounts.len() / 2 <= MAX_CRANK_DEACTIVATE_POSITIONS,
+            ErrorCode::TooManyPositions
+        );
+        let now = Clock::get()?.unix_timestamp;
+        let cfg = &mut ctx.accounts.config;
+        let caller = ctx.accounts.caller.to_account_info();
+        let system_program = ctx.accounts.system_program.to_account_info();
+
+        let mut count: u8 = 0;
+        for pair in accounts.chunks(2) {
+            let (position_info, profile_info) = (&pair[0], &pair[1]);
+            let mut position = load_position_any(position_info)?;
+            if position.deactivated || now < position.end_ts {
+                continue;
+            }
+            let grace_deadline = grace_deadline_ts(position.end_ts, cfg.seconds_per_day)?;
+            if now <= grace_deadline {
+                continue;
+            }
+            let mut profile = load_user_profile_any(profile_info)?;
+            require_keys_eq!(profile.owner, position.owner, ErrorCode::Unauthorized);
+            update_user_xp(&mut profile, now)?;
+            let rig_type = position_rig_type(&position, cfg)?;
+            apply_level_updates_to_position(
+                &mut position,
+                &profile,
+                rig_type,
+                now,
+                cfg.acc_mind_per_hp,
+                bonus_table.as_ref(),
+            )?;
+            apply_pending_buff(cfg, &mut position, now, &mut profile)?;
+            deactivate_position_state(cfg, &mut position, position_info.key(), &mut profile, now)?;
+            sync_profile_level_bonus(cfg, &mut profile, now, bonus_table.as_ref())?;
+            ensure_position_v2(position_info, &caller, &system_program)?;
+            save_position(position_info, &position)?;
+            save_user_profile(profile_info, &profile)?;
+            count += 1;
+        }
+
+        if count > 0 && cfg.crank_reward > 0 {
+            // Best effort: a thin treasury pays what it can rather than failing the crank.
+            let reward = cfg
+                .crank_reward
+                .checked_mul(count as u64)
+                .ok_or(ErrorCode::MathOverflow)?
+                .min(vault_available_lamports(&ctx.accounts.treasury_vault)?);
+            if reward > 0 {
+                transfer_lamports(
+                    &ctx.accounts.treasury_vault.to_account_info(),
+                    &caller,
+                    reward,
+                )?;
+            }
+        }
+        emit!(CrankDeactivated { count });
+        Ok(())
+    }
+
+    /// Read-only preview of `claim_mind` for one position: settles a scratch copy of config
+    /// and profile at the current slot and emits `SimulatedClaim` instead of minting.
+    pub fn simulate_claim_mind(ctx: Context<SimulateClaimMind>) -> Result<()> {
+        let bonus_table = load_level_bonus_table(
+            ctx.accounts.level_bonus_table.as_deref(),
+            &ctx.accounts.config,
+        )?;
+        let now = Clock::get()?.unix_timestamp;
+        let owner = ctx.accounts.owner.key();
+        let mut cfg: Config = (**ctx.accounts.config).clone();
+        let mut profile = load_user_profile_any(&ctx.accounts.user_profile)?;
+        require_keys_eq!(profile.owner, owner, ErrorCode::Unauthorized);
+        ensure_profile_hp_scaled(&mut profile)?;
+        update_user_xp(&mut profile, now)?;
+        let mut position = load_position_any(&ctx.accounts.position)?;
+        require_keys_eq!(position.owner, owner, ErrorCode::InvalidPositionOwner);
+
+        let (pending, hp_effective) = simulate_position_claim(
+            &mut cfg,
+            &mut position,
+            &mut profile,
+            now,
+            bonus_table.as_ref(),
+        )?;
+        emit!(SimulatedClaim {
+            owner,
+            position: ctx.accounts.position.key(),
+            pending,
+            expired: position.expired,
+            hp_effective,
+        });
+        Ok(())
+    }
+
+    /// Floor on a new position's base HP, in the same scaled units as `contract_terms`.
+    pub fn admin_set_min_position_hp(
+        ctx: Context<AdminUpdateConfig>,
+        min_position_hp: u64,
+    ) -> Result<()> {
+        let cfg = &mut ctx.accounts.config;
+        require_keys_eq!(cfg.admin, ctx.accounts.admin.key(), ErrorCode::Unauthorized);
+        cfg.min_position_hp = min_position_hp;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(params: InitConfigParams)]
+pub struct InitConfig<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub admin: Signer<'info>,
+    /// CHECK: ProgramData account for the upgrade authority check.
+    pub program_data: UncheckedAccount<'info>,
+    /// CHECK: PDA derived from VAULT_SEED/bump used as vault authority.
+    /// CHECK: PDA derived from VAULT_SEED/bump used as vault authority.
+    #[account(seeds = [VAULT_SEED], bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + Config::INIT_SPACE,
+        seeds = [CONFIG_SEED],
+        bump
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        constraint = mind_mint.mint_authority == COption::Some(vault_authority.key())
+    )]
+    pub mind_mint: Box<Account<'info, Mint>>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + NativeVault::INIT_SPACE,
+        seeds = [STAKING_REWARD_VAULT_SEED],
+        bump
+    )]
+    pub staking_reward_vault: Box<Account<'info, NativeVault>>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + NativeVault::INIT_SPACE,
+        seeds = [TREASURY_VAULT_SEED],
+        bump
+    )]
+    pub treasury_vault: Box<Account<'info, NativeVault>>,
+    #[account(
+        constraint = staking_mind_vault.owner == vault_authority.key(),
+        constraint = staking_mind_vault.mint == mind_mint.key()
+    )]
+    pub staking_mind_vault: Box<Account<'info, TokenAccount>>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct InitConfigParams {
+    pub emission_per_sec: u64,
+    pub max_effective_hp: u64,
+    pub seconds_per_day: u64,
+    /// Zero selects `STAKING_SHARE_BPS`.
+    pub staking_share_bps: u16,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct InitRigBuffConfigParams {
+    pub mind_per_hp_per_day: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct RepairPositionParams {
+    pub reward_debt: Option<u128>,
+    pub final_acc_mind_per_hp: Option<u128>,
+    pub expired: Option<bool>,
+    pub deactivated: Option<bool>,
+    pub buff_level: Option<u8>,
+    pub last_level_applied: Option<u8>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct UpdateRigBuffConfigParams {
+    pub mind_per_hp_per_day: u64,
+    pub min_buff_upgrade_interval_sec: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct MetadataParams {
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub seller_fee_basis_points: u16,
+}
+
+#[derive(Accounts)]
+pub struct InitLevelConfig<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bumps.config
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + LevelConfig::INIT_SPACE,
+        seeds = [LEVEL_CONFIG_SEED],
+        bump
+    )]
+    pub level_config: Box<Account<'info, LevelConfig>>,
+    #[account(
+        constraint = mind_mint.key() == config.mind_mint
+    )]
+    pub mind_mint: Account<'info, Mint>,
+    #[account(
+        constraint = mind_burn_vault.mint == mind_mint.key()
+    )]
+    pub mind_burn_vault: Account<'info, TokenAccount>,
+    #[account(
+        constraint = mind_treasury_vault.mint == mind_mint.key()
+    )]
+    pub mind_treasury_vault: Account<'info, TokenAccount>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitRigBuffConfig<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bumps.config
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + RigBuffConfig::INIT_SPACE,
+        seeds = [RIG_BUFF_CONFIG_SEED],
+        bump
+    )]
+    pub rig_buff_config: Box<Account<'info, RigBuffConfig>>,
+    #[account(
+        constraint = mind_mint.key() == config.mind_mint
+    )]
+    pub mind_mint: Account<'info, Mint>,
+    #[account(
+        constraint = mind_burn_vault.mint == mind_mint.key()
+    )]
+    pub mind_burn_vault: Account<'info, TokenAccount>,
+    #[account(
+        constraint = mind_treasury_vault.mint == mind_mint.key()
+    )]
+    pub mind_treasury_vault: Account<'info, TokenAccount>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AdminUpdateRigBuffConfig<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bumps.config
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        mut,
+        seeds = [RIG_BUFF_CONFIG_SEED],
+        bump
+    )]
+    /// CHECK: Manual decoding supports the pre-cooldown layout.
+    pub rig_buff_config: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(contract_type: u8, position_index: u64)]
+pub struct BuyContract<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED],
+        bump = config.bumps.config
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        mut,
+        seeds = [PROFILE_SEED, owner.key().as_ref()],
+        bump
+    )]
+    /// CHECK: PDA derived from PROFILE_SEED; validated in instruction handlers.
+    pub user_profile: UncheckedAccount<'info>,
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + MinerPosition::INIT_SPACE,
+        seeds = [POSITION_SEED, owner.key().as_ref(), position_index.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub position: Box<Account<'info, MinerPosition>>,
+    #[account(
+        mut,
+        seeds = [STAKING_REWARD_VAULT_SEED],
+        bump,
+        constraint = staking_reward_vault.key() == config.staking_reward_vault
+    )]
+    pub staking_reward_vault: Account<'info, NativeVault>,
+    #[account(
+        mut,
+        seeds = [TREASURY_VAULT_SEED],
+        bump,
+        constraint = treasury_vault.key() == config.treasury_vault
+    )]
+    pub treasury_vault: Account<'info, NativeVault>,
+    pub system_program: Program<'info, System>,
+    #[account(seeds = [LEVEL_BONUS_TABLE_SEED], bump)]
+    /// CHECK: PDA derived from LEVEL_BONUS_TABLE_SEED; read by `load_level_bonus_table`.
+    pub level_bonus_table: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct RenewRig<'info> {
+    #[account(mut)]
+    /// CHECK: Must sign unless `delegate` signs with a matching `delegate_record`.
+    pub owner: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED],
+        bump = config.bumps.config
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        mut,
+        seeds = [PROFILE_SEED, owner.key().as_ref()],
+        bump
+    )]
+    /// CHECK: PDA derived from PROFILE_SEED; validated in instruction handlers.
+    pub user_profile: UncheckedAccount<'info>,
+    #[account(mut)]
+    /// CHECK: Manual position decoding supports legacy sizes.
+    pub position: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [STAKING_REWARD_VAULT_SEED],
+        bump,
+        constraint = staking_reward_vault.key() == config.staking_reward_vault
+    )]
+    pub staking_reward_vault: Account<'info, NativeVault>,
+    #[account(
+        mut,
+        seeds = [TREASURY_VAULT_SEED],
+        bump,
+        constraint = treasury_vault.key() == config.treasury_vault
+    )]
+    pub treasury_vault: Account<'info, NativeVault>,
+    pub system_program: Program<'info, System>,
+    #[account(seeds = [LEVEL_BONUS_TABLE_SEED], bump)]
+    /// CHECK: PDA derived from LEVEL_BONUS_TABLE_SEED; read by `load_level_bonus_table`.
+    pub level_bonus_table: Option<UncheckedAccount<'info>>,
+    #[account(mut)]
+    pub delegate: Option<Signer<'info>>,
+    pub delegate_record: Option<Account<'info, DelegateRecord>>,
+}
+
+#[derive(Accounts)]
+pub struct RenewRigWithBuff<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED],
+        bump = config.bumps.config
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(seeds = [RIG_BUFF_CONFIG_SEED], bump)]
+    /// CHECK: Manual decoding supports the pre-cooldown layout.
+    pub rig_buff_config: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [PROFILE_SEED, owner.key().as_ref()],
+        bump
+    )]
+    /// CHECK: PDA derived from PROFILE_SEED; validated in instruction handlers.
+    pub user_profile: UncheckedAccount<'info>,
+    #[account(mut)]
+    /// CHECK: Manual position decoding supports legacy sizes.
+    pub position: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [STAKING_REWARD_VAULT_SEED],
+        bump,
+        constraint = staking_reward_vault.key() == config.staking_reward_vault
+    )]
+    pub staking_reward_vault: Account<'info, NativeVault>,
+    #[account(
+        mut,
+        seeds = [TREASURY_VAULT_SEED],
+        bump,
+        constraint = treasury_vault.key() == config.treasury_vault
+    )]
+    pub treasury_vault: Account<'info, NativeVault>,
+    #[account(
+        mut,
+        constraint = mind_mint.key() == config.mind_mint
+    )]
+    pub mind_mint: Account<'info, Mint>,
+    #[account(
+        mut,
+        constraint = owner_mind_ata.owner == owner.key(),
+        constraint = owner_mind_ata.mint == config.mind_mint
+    )]
+    pub owner_mind_ata: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = burn_mind_vault.mint == config.mind_mint
+    )]
+    pub burn_mind_vault: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = treasury_mind_vault.mint == config.mind_mint
+    )]
+    pub treasury_mind_vault: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    #[account(seeds = [LEVEL_BONUS_TABLE_SEED], bump)]
+    /// CHECK: PDA derived from LEVEL_BONUS_TABLE_SEED; read by `load_level_bonus_table`.
+    pub level_bonus_table: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimMind<'info> {
+    #[account(mut)]
+    /// CHECK: Must sign unless `delegate` signs with a matching `delegate_record`.
+    pub owner: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED],
+        bump = config.bumps.config
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        mut,
+        seeds = [PROFILE_SEED, owner.key().as_ref()],
+        bump
+    )]
+    /// CHECK: PDA derived from PROFILE_SEED; validated in instruction handlers.
+    pub user_profile: UncheckedAccount<'info>,
+    #[account(mut)]
+    /// CHECK: Manual position decoding supports legacy sizes.
+    pub position: UncheckedAccount<'info>,
+    #[account(seeds = [VAULT_SEED], bump = config.bumps.vault_authority)]
+    /// CHECK: PDA derived from VAULT_SEED/bump used as vault authority.
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        constraint = mind_mint.key() == config.mind_mint
+    )]
+    pub mind_mint: Account<'info, Mint>,
+    #[account(
+        mut,
+        constraint = user_mind_ata.owner == owner.key(),
+        constraint = user_mind_ata.mint == mind_mint.key()
+    )]
+    pub user_mind_ata: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    #[account(seeds = [LEVEL_BONUS_TABLE_SEED], bump)]
+    /// CHECK: PDA derived from LEVEL_BONUS_TABLE_SEED; read by `load_level_bonus_table`.
+    pub level_bonus_table: Option<UncheckedAccount<'info>>,
+    #[account(mut)]
+    pub delegate: Option<Signer<'info>>,
+    pub delegate_record: Option<Account<'info, DelegateRecord>>,
+}
+
+#[derive(Accounts)]
+pub struct BatchClaimMind<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED],
+        bump = config.bumps.config
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        mut,
+        seeds = [PROFILE_SEED, owner.key().as_ref()],
+        bump
+    )]
+    /// CHECK: PDA derived from PROFILE_SEED; validated in instruction handlers.
+    pub user_profile: UncheckedAccount<'info>,
+    #[account(seeds = [VAULT_SEED], bump = config.bumps.vault_authority)]
+    /// CHECK: PDA derived from VAULT_SEED/bump used as vault authority.
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        constraint = mind_mint.key() == config.mind_mint
+    )]
+    pub mind_mint: Account<'info, Mint>,
+    #[account(
+        mut,
+        constraint = user_mind_ata.owner == owner.key(),
+        constraint = user_mind_ata.mint == mind_mint.key()
+    )]
+    pub user_mind_ata: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    #[account(seeds = [LEVEL_BONUS_TABLE_SEED], bump)]
+    /// CHECK: PDA derived from LEVEL_BONUS_TABLE_SEED; read by `load_level_bonus_table`.
+    pub level_bonus_table: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct DeactivatePosition<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED],
+        bump = config.bumps.config
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(mut)]
+    /// CHECK: Manual position decoding supports legacy sizes.
+    pub position: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [PROFILE_SEED, owner.key().as_ref()],
+        bump
+    )]
+    /// CHECK: PDA derived from PROFILE_SEED; validated in instruction handlers.
+    pub user_profile: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+    #[account(seeds = [LEVEL_BONUS_TABLE_SEED], bump)]
+    /// CHECK: PDA derived from LEVEL_BONUS_TABLE_SEED; read by `load_level_bonus_table`.
+    pub level_bonus_table: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct LevelUp<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED],
+        bump = config.bumps.config
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        seeds = [LEVEL_CONFIG_SEED],
+        bump = level_config.bump,
+        constraint = level_config.mind_mint == config.mind_mint
+    )]
+    pub level_config: Box<Account<'info, LevelConfig>>,
+    #[account(
+        mut,
+        constraint = mind_mint.key() == config.mind_mint
+    )]
+    pub mind_mint: Account<'info, Mint>,
+    #[account(
+        mut,
+        seeds = [PROFILE_SEED, owner.key().as_ref()],
+        bump
+    )]
+    /// CHECK: PDA derived from PROFILE_SEED; validated in instruction handlers.
+    pub user_profile: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        constraint = owner_mind_ata.owner == owner.key(),
+        constraint = owner_mind_ata.mint == config.mind_mint
+    )]
+    pub owner_mind_ata: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = burn_mind_vault.key() == level_config.mind_burn_vault,
+        constraint = burn_mind_vault.mint == config.mind_mint
+    )]
+    pub burn_mind_vault: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = treasury_mind_vault.key() == level_config.mind_treasury_vault,
+        constraint = treasury_mind_vault.mint == config.mind_mint
+    )]
+    pub treasury_mind_vault: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    #[account(seeds = [LEVEL_BONUS_TABLE_SEED], bump)]
+    /// CHECK: PDA derived from LEVEL_BONUS_TABLE_SEED; read by `load_level_bonus_table`.
+    pub level_bonus_table: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct SyncProfile<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bumps.config
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        mut,
+        seeds = [PROFILE_SEED, owner.key().as_ref()],
+        bump
+    )]
+    /// CHECK: PDA derived from PROFILE_SEED; validated in instruction handlers.
+    pub user_profile: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-#[instruction(params: InitConfigParams)]
-pub struct InitConfig<'info> {
+pub struct StakeMind<'info> {
     #[account(mut)]
-    pub payer: Signer<'info>,
-    pub admin: Signer<'info>,
-    /// CHECK: ProgramData account for the upgrade authority check.
-    pub program_data: UncheckedAccount<'info>,
-    /// CHECK: PDA derived from VAULT_SEED/bump used as vault authority.
-    /// CHECK: PDA derived from VAULT_SEED/bump used as vault authority.
-    #[account(seeds = [VAULT_SEED], bump)]
-    pub vault_authority: UncheckedAccount<'info>,
+    /// CHECK: Must sign unless `delegate` signs with a matching `delegate_record`.
+    pub owner: UncheckedAccount<'info>,
     #[account(
-        init,
-        payer = payer,
-        space = 8 + Config::INIT_SPACE,
+        mut,
         seeds = [CONFIG_SEED],
-        bump
+        bump = config.bumps.config
     )]
     pub config: Box<Account<'info, Config>>,
     #[account(
-        constraint = mind_mint.mint_authority == COption::Some(vault_authority.key())
-    )]
-    pub mind_mint: Box<Account<'info, Mint>>,
-    #[account(
-        init,
-        payer = payer,
-        space = 8 + NativeVault::INIT_SPACE,
-        seeds = [STAKING_REWARD_VAULT_SEED],
+        mut,
+        seeds = [PROFILE_SEED, owner.key().as_ref()],
         bump
     )]
-    pub staking_reward_vault: Box<Account<'info, NativeVault>>,
+    /// CHECK: PDA derived from PROFILE_SEED; validated in instruction handlers.
+    pub user_profile: UncheckedAccount<'info>,
     #[account(
-        init,
-        payer = payer,
-        space = 8 + NativeVault::INIT_SPACE,
-        seeds = [TREASURY_VAULT_SEED],
+        init_if_needed,
+        payer = owner,
+        space = 8 + UserStake::INIT_SPACE,
+        seeds = [STAKE_SEED, owner.key().as_ref()],
         bump
     )]
-    pub treasury_vault: Box<Account<'info, NativeVault>>,
+    pub user_stake: Box<Account<'info, UserStake>>,
+    #[account(seeds = [VAULT_SEED], bump = config.bumps.vault_authority)]
+    /// CHECK: PDA derived from VAULT_SEED/bump used as vault authority.
+    pub vault_authority: UncheckedAccount<'info>,
     #[account(
+        mut,
+        constraint = staking_mind_vault.key() == config.staking_mind_vault,
         constraint = staking_mind_vault.owner == vault_authority.key(),
-        constraint = staking_mind_vault.mint == mind_mint.key()
+        constraint = staking_mind_vault.mint == config.mind_mint
     )]
-    pub staking_mind_vault: Box<Account<'info, TokenAccount>>,
+    pub staking_mind_vault: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = owner_mind_ata.owner == owner.key(),
+        constraint = owner_mind_ata.mint == config.mind_mint
+    )]
+    pub owner_mind_ata: Account<'info, TokenAccount>,
     pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
-    pub rent: Sysvar<'info, Rent>,
-}
-
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
-pub struct InitConfigParams {
-    pub emission_per_sec: u64,
-    pub max_effective_hp: u64,
-    pub seconds_per_day: u64,
-}
-
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
-pub struct InitRigBuffConfigParams {
-    pub mind_per_hp_per_day: u64,
-}
-
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
-pub struct UpdateRigBuffConfigParams {
-    pub mind_per_hp_per_day: u64,
+    #[account(mut)]
+    pub delegate: Option<Signer<'info>>,
+    pub delegate_record: Option<Account<'info, DelegateRecord>>,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
-pub struct MetadataParams {
-    pub name: String,
-    pub symbol: String,
-    pub uri: String,
-    pub seller_fee_basis_points: u16,
+#[derive(Accounts)]
+pub struct UnstakeMind<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED],
+        bump = config.bumps.config
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        mut,
+        seeds = [STAKE_SEED, owner.key().as_ref()],
+        bump = user_stake.bump,
+        constraint = user_stake.owner == owner.key()
+    )]
+    pub user_stake: Box<Account<'info, UserStake>>,
+    #[account(seeds = [VAULT_SEED], bump = config.bumps.vault_authority)]
+    /// CHECK: PDA derived from VAULT_SEED/bump used as vault authority.
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        constraint = staking_mind_vault.key() == config.staking_mind_vault,
+        constraint = staking_mind_vault.owner == vault_authority.key(),
+        constraint = staking_mind_vault.mint == config.mind_mint
+    )]
+    pub staking_mind_vault: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = config.mind_mint == mind_mint.key()
+    )]
+    pub mind_mint: Account<'info, Mint>,
+    #[account(
+        mut,
+        constraint = owner_mind_ata.owner == owner.key(),
+        constraint = owner_mind_ata.mint == config.mind_mint
+    )]
+    pub owner_mind_ata: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
 }
 
 #[derive(Accounts)]
-pub struct InitLevelConfig<'info> {
+pub struct RequestUnstake<'info> {
     #[account(mut)]
-    pub admin: Signer<'info>,
+    pub owner: Signer<'info>,
     #[account(
+        mut,
         seeds = [CONFIG_SEED],
         bump = config.bumps.config
     )]
     pub config: Box<Account<'info, Config>>,
     #[account(
-        init_if_needed,
-        payer = admin,
-        space = 8 + LevelConfig::INIT_SPACE,
-        seeds = [LEVEL_CONFIG_SEED],
-        bump
+        mut,
+        seeds = [STAKE_SEED, owner.key().as_ref()],
+        bump = user_stake.bump,
+        constraint = user_stake.owner == owner.key()
     )]
-    pub level_config: Box<Account<'info, LevelConfig>>,
+    pub user_stake: Box<Account<'info, UserStake>>,
+    #[account(seeds = [VAULT_SEED], bump = config.bumps.vault_authority)]
+    /// CHECK: PDA derived from VAULT_SEED/bump used as vault authority.
+    pub vault_authority: UncheckedAccount<'info>,
     #[account(
-        constraint = mind_mint.key() == config.mind_mint
+        mut,
+        constraint = staking_mind_vault.key() == config.staking_mind_vault,
+        constraint = staking_mind_vault.owner == vault_authority.key(),
+        constraint = staking_mind_vault.mint == config.mind_mint
+    )]
+    pub staking_mind_vault: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = config.mind_mint == mind_mint.key()
     )]
     pub mind_mint: Account<'info, Mint>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CompleteUnstake<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
     #[account(
-        constraint = mind_burn_vault.mint == mind_mint.key()
+        seeds = [CONFIG_SEED],
+        bump = config.bumps.config
     )]
-    pub mind_burn_vault: Account<'info, TokenAccount>,
+    pub config: Box<Account<'info, Config>>,
     #[account(
-        constraint = mind_treasury_vault.mint == mind_mint.key()
+        mut,
+        seeds = [STAKE_SEED, owner.key().as_ref()],
+        bump = user_stake.bump,
+        constraint = user_stake.owner == owner.key()
     )]
-    pub mind_treasury_vault: Account<'info, TokenAccount>,
-    pub system_program: Program<'info, System>,
+    pub user_stake: Box<Account<'info, UserStake>>,
+    #[account(seeds = [VAULT_SEED], bump = config.bumps.vault_authority)]
+    /// CHECK: PDA derived from VAULT_SEED/bump used as vault authority.
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        constraint = staking_mind_vault.key() == config.staking_mind_vault,
+        constraint = staking_mind_vault.owner == vault_authority.key(),
+        constraint = staking_mind_vault.mint == config.mind_mint
+    )]
+    pub staking_mind_vault: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = owner_mind_ata.owner == owner.key(),
+        constraint = owner_mind_ata.mint == config.mind_mint
+    )]
+    pub owner_mind_ata: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
 }
 
 #[derive(Accounts)]
-pub struct InitRigBuffConfig<'info> {
+pub struct ClaimStakedMind<'info> {
     #[account(mut)]
-    pub admin: Signer<'info>,
+    pub owner: Signer<'info>,
     #[account(
+        mut,
         seeds = [CONFIG_SEED],
         bump = config.bumps.config
     )]
     pub config: Box<Account<'info, Config>>,
     #[account(
-        init_if_needed,
-        payer = admin,
-        space = 8 + RigBuffConfig::INIT_SPACE,
-        seeds = [RIG_BUFF_CONFIG_SEED],
-        bump
+        mut,
+        seeds = [STAKE_SEED, owner.key().as_ref()],
+        bump = user_stake.bump,
+        constraint = user_stake.owner == owner.key()
     )]
-    pub rig_buff_config: Box<Account<'info, RigBuffConfig>>,
+    pub user_stake: Box<Account<'info, UserStake>>,
+    #[account(seeds = [VAULT_SEED], bump = config.bumps.vault_authority)]
+    /// CHECK: PDA derived from VAULT_SEED/bump used as vault authority.
+    pub vault_authority: UncheckedAccount<'info>,
     #[account(
+        mut,
         constraint = mind_mint.key() == config.mind_mint
     )]
     pub mind_mint: Account<'info, Mint>,
     #[account(
-        constraint = mind_burn_vault.mint == mind_mint.key()
+        mut,
+        constraint = owner_mind_ata.owner == owner.key(),
+        constraint = owner_mind_ata.mint == config.mind_mint
     )]
-    pub mind_burn_vault: Account<'info, TokenAccount>,
+    pub owner_mind_ata: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateUserStake<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
     #[account(
-        constraint = mind_treasury_vault.mint == mind_mint.key()
+        mut,
+        seeds = [CONFIG_SEED],
+        bump = config.bumps.config
     )]
-    pub mind_treasury_vault: Account<'info, TokenAccount>,
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        mut,
+        seeds = [STAKE_SEED, owner.key().as_ref()],
+        bump
+    )]
+    /// CHECK: Manual decoding supports the pre-split UserStake layout.
+    pub user_stake: UncheckedAccount<'info>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct AdminUpdateRigBuffConfig<'info> {
+pub struct ClaimXnt<'info> {
     #[account(mut)]
-    pub admin: Signer<'info>,
+    pub owner: Signer<'info>,
     #[account(
-        seeds = [RIG_BUFF_CONFIG_SEED],
-        bump = rig_buff_config.bump
+        mut,
+        seeds = [CONFIG_SEED],
+        bump = config.bumps.config
     )]
-    pub rig_buff_config: Box<Account<'info, RigBuffConfig>>,
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        mut,
+        seeds = [PROFILE_SEED, owner.key().as_ref()],
+        bump
+    )]
+    /// CHECK: PDA derived from PROFILE_SEED; validated in instruction handlers.
+    pub user_profile: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [STAKE_SEED, owner.key().as_ref()],
+        bump = user_stake.bump,
+        constraint = user_stake.owner == owner.key()
+    )]
+    pub user_stake: Box<Account<'info, UserStake>>,
+    #[account(
+        mut,
+        seeds = [STAKING_REWARD_VAULT_SEED],
+        bump,
+        constraint = staking_reward_vault.key() == config.staking_reward_vault
+    )]
+    pub staking_reward_vault: Account<'info, NativeVault>,
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-#[instruction(contract_type: u8, position_index: u64)]
-pub struct BuyContract<'info> {
-    #[account(mut)]
-    pub owner: Signer<'info>,
+pub struct RollEpoch<'info> {
+    /// Config admin or one of `config.epoch_rollers`.
+    pub admin: Signer<'info>,
     #[account(
         mut,
         seeds = [CONFIG_SEED],
         bump = config.bumps.config
     )]
     pub config: Box<Account<'info, Config>>,
-    #[account(
-        mut,
-        seeds = [PROFILE_SEED, owner.key().as_ref()],
-        bump
-    )]
-    /// CHECK: PDA derived from PROFILE_SEED; validated in instruction handlers.
-    pub user_profile: UncheckedAccount<'info>,
-    #[account(
-        init,
-        payer = owner,
-        space = 8 + MinerPosition::INIT_SPACE,
-        seeds = [POSITION_SEED, owner.key().as_ref(), position_index.to_le_bytes().as_ref()],
-        bump
-    )]
-    pub position: Box<Account<'info, MinerPosition>>,
     #[account(
         mut,
         seeds = [STAKING_REWARD_VAULT_SEED],
@@ -1794,244 +3975,271 @@ pub struct BuyContract<'info> {
         constraint = staking_reward_vault.key() == config.staking_reward_vault
     )]
     pub staking_reward_vault: Account<'info, NativeVault>,
+}
+
+#[derive(Accounts)]
+pub struct AdminMigrateConfig<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
     #[account(
         mut,
-        seeds = [TREASURY_VAULT_SEED],
-        bump,
-        constraint = treasury_vault.key() == config.treasury_vault
+        seeds = [CONFIG_SEED],
+        bump
     )]
-    pub treasury_vault: Account<'info, NativeVault>,
+    /// CHECK: Manual decoding supports legacy Config layouts.
+    pub config: UncheckedAccount<'info>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct RenewRig<'info> {
+pub struct AdminUpdateConfig<'info> {
     #[account(mut)]
-    pub owner: Signer<'info>,
+    pub admin: Signer<'info>,
     #[account(
         mut,
         seeds = [CONFIG_SEED],
         bump = config.bumps.config
     )]
     pub config: Box<Account<'info, Config>>,
+}
+
+#[derive(Accounts)]
+pub struct AdminFixAccumulator<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
     #[account(
         mut,
-        seeds = [PROFILE_SEED, owner.key().as_ref()],
-        bump
+        seeds = [CONFIG_SEED],
+        bump = config.bumps.config,
+        has_one = admin
     )]
-    /// CHECK: PDA derived from PROFILE_SEED; validated in instruction handlers.
+    pub config: Box<Account<'info, Config>>,
+    /// CHECK: PDA derived from PROFILE_SEED; validated in the instruction handler.
+    #[account(mut)]
     pub user_profile: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AdminRescaleLegacyPositions<'info> {
     #[account(mut)]
-    /// CHECK: Manual position decoding supports legacy sizes.
-    pub position: UncheckedAccount<'info>,
+    pub admin: Signer<'info>,
     #[account(
-        mut,
-        seeds = [STAKING_REWARD_VAULT_SEED],
-        bump,
-        constraint = staking_reward_vault.key() == config.staking_reward_vault
+        seeds = [CONFIG_SEED],
+        bump = config.bumps.config,
+        has_one = admin
     )]
-    pub staking_reward_vault: Account<'info, NativeVault>,
+    pub config: Box<Account<'info, Config>>,
     #[account(
-        mut,
-        seeds = [TREASURY_VAULT_SEED],
-        bump,
-        constraint = treasury_vault.key() == config.treasury_vault
+        seeds = [HP_SCALE_SEED],
+        bump = hp_scale_config.bump
     )]
-    pub treasury_vault: Account<'info, NativeVault>,
+    pub hp_scale_config: Box<Account<'info, HpScaleConfig>>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct RenewRigWithBuff<'info> {
+pub struct AdminRepairPosition<'info> {
     #[account(mut)]
-    pub owner: Signer<'info>,
+    pub admin: Signer<'info>,
     #[account(
-        mut,
         seeds = [CONFIG_SEED],
-        bump = config.bumps.config
+        bump = config.bumps.config,
+        has_one = admin
     )]
     pub config: Box<Account<'info, Config>>,
-    #[account(
-        seeds = [RIG_BUFF_CONFIG_SEED],
-        bump = rig_buff_config.bump,
-        constraint = rig_buff_config.mind_mint == config.mind_mint
-    )]
-    pub rig_buff_config: Box<Account<'info, RigBuffConfig>>,
-    #[account(
-        mut,
-        seeds = [PROFILE_SEED, owner.key().as_ref()],
-        bump
-    )]
-    /// CHECK: PDA derived from PROFILE_SEED; validated in instruction handlers.
-    pub user_profile: UncheckedAccount<'info>,
     #[account(mut)]
     /// CHECK: Manual position decoding supports legacy sizes.
     pub position: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AdminSetNetworkHpActive<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
     #[account(
         mut,
-        seeds = [STAKING_REWARD_VAULT_SEED],
-        bump,
-        constraint = staking_reward_vault.key() == config.staking_reward_vault
+        seeds = [CONFIG_SEED],
+        bump = config.bumps.config
     )]
-    pub staking_reward_vault: Account<'info, NativeVault>,
+    pub config: Box<Account<'info, Config>>,
+}
+
+#[derive(Accounts)]
+pub struct AdminEnableHpScaling<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
     #[account(
         mut,
-        seeds = [TREASURY_VAULT_SEED],
-        bump,
-        constraint = treasury_vault.key() == config.treasury_vault
+        seeds = [CONFIG_SEED],
+        bump = config.bumps.config
     )]
-    pub treasury_vault: Account<'info, NativeVault>,
+    pub config: Box<Account<'info, Config>>,
     #[account(
-        mut,
-        constraint = mind_mint.key() == config.mind_mint
+        init_if_needed,
+        payer = admin,
+        space = 8 + HpScaleConfig::INIT_SPACE,
+        seeds = [HP_SCALE_SEED],
+        bump
     )]
-    pub mind_mint: Account<'info, Mint>,
+    pub hp_scale_config: Box<Account<'info, HpScaleConfig>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AdminUseNativeXnt<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
     #[account(
         mut,
-        constraint = owner_mind_ata.owner == owner.key(),
-        constraint = owner_mind_ata.mint == config.mind_mint
+        seeds = [CONFIG_SEED],
+        bump = config.bumps.config
     )]
-    pub owner_mind_ata: Account<'info, TokenAccount>,
+    pub config: Box<Account<'info, Config>>,
     #[account(
-        mut,
-        constraint = burn_mind_vault.key() == rig_buff_config.mind_burn_vault,
-        constraint = burn_mind_vault.mint == config.mind_mint
+        init_if_needed,
+        payer = admin,
+        space = 8 + NativeVault::INIT_SPACE,
+        seeds = [STAKING_REWARD_VAULT_SEED],
+        bump
     )]
-    pub burn_mind_vault: Account<'info, TokenAccount>,
+    pub staking_reward_vault: Account<'info, NativeVault>,
     #[account(
-        mut,
-        constraint = treasury_mind_vault.key() == rig_buff_config.mind_treasury_vault,
-        constraint = treasury_mind_vault.mint == config.mind_mint
+        init_if_needed,
+        payer = admin,
+        space = 8 + NativeVault::INIT_SPACE,
+        seeds = [TREASURY_VAULT_SEED],
+        bump
     )]
-    pub treasury_mind_vault: Account<'info, TokenAccount>,
-    pub token_program: Program<'info, Token>,
+    pub treasury_vault: Account<'info, NativeVault>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct ClaimMind<'info> {
+pub struct AdminUseSplXnt<'info> {
     #[account(mut)]
-    pub owner: Signer<'info>,
+    pub admin: Signer<'info>,
     #[account(
         mut,
         seeds = [CONFIG_SEED],
         bump = config.bumps.config
     )]
     pub config: Box<Account<'info, Config>>,
+    #[account(seeds = [VAULT_SEED], bump = config.bumps.vault_authority)]
+    /// CHECK: PDA derived from VAULT_SEED/bump used as vault authority.
+    pub vault_authority: UncheckedAccount<'info>,
+    pub xnt_mint: Account<'info, Mint>,
     #[account(
-        mut,
-        seeds = [PROFILE_SEED, owner.key().as_ref()],
-        bump
+        constraint = staking_xnt_vault.owner == vault_authority.key(),
+        constraint = staking_xnt_vault.mint == xnt_mint.key()
     )]
-    /// CHECK: PDA derived from PROFILE_SEED; validated in instruction handlers.
-    pub user_profile: UncheckedAccount<'info>,
+    pub staking_xnt_vault: Account<'info, TokenAccount>,
+}
+
+#[derive(Accounts)]
+pub struct AdminSetMetadata<'info> {
     #[account(mut)]
-    /// CHECK: Manual position decoding supports legacy sizes.
-    pub position: UncheckedAccount<'info>,
+    pub admin: Signer<'info>,
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bumps.config
+    )]
+    pub config: Box<Account<'info, Config>>,
     #[account(seeds = [VAULT_SEED], bump = config.bumps.vault_authority)]
-    /// CHECK: PDA derived from VAULT_SEED/bump used as vault authority.
+    /// CHECK: PDA used as mint authority signer for metadata creation.
     pub vault_authority: UncheckedAccount<'info>,
     #[account(
-        mut,
         constraint = mind_mint.key() == config.mind_mint
     )]
     pub mind_mint: Account<'info, Mint>,
-    #[account(
-        mut,
-        constraint = user_mind_ata.owner == owner.key(),
-        constraint = user_mind_ata.mint == mind_mint.key()
-    )]
-    pub user_mind_ata: Account<'info, TokenAccount>,
-    pub token_program: Program<'info, Token>,
+    #[account(mut)]
+    /// CHECK: Metaplex metadata PDA for the MIND mint.
+    pub metadata: UncheckedAccount<'info>,
+    #[account(address = METADATA_PROGRAM_ID)]
+    /// CHECK: Metaplex token metadata program.
+    pub metadata_program: UncheckedAccount<'info>,
     pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
 }
 
 #[derive(Accounts)]
-pub struct DeactivatePosition<'info> {
+pub struct AdminWithdrawStakingRewards<'info> {
     #[account(mut)]
-    pub owner: Signer<'info>,
+    pub admin: Signer<'info>,
     #[account(
         mut,
         seeds = [CONFIG_SEED],
         bump = config.bumps.config
     )]
     pub config: Box<Account<'info, Config>>,
-    #[account(mut)]
-    /// CHECK: Manual position decoding supports legacy sizes.
-    pub position: UncheckedAccount<'info>,
     #[account(
         mut,
-        seeds = [PROFILE_SEED, owner.key().as_ref()],
-        bump
+        seeds = [STAKING_REWARD_VAULT_SEED],
+        bump = staking_reward_vault.bump,
+        constraint = staking_reward_vault.key() == config.staking_reward_vault
     )]
-    /// CHECK: PDA derived from PROFILE_SEED; validated in instruction handlers.
-    pub user_profile: UncheckedAccount<'info>,
+    pub staking_reward_vault: Account<'info, NativeVault>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct LevelUp<'info> {
+pub struct AdminWithdrawTreasury<'info> {
     #[account(mut)]
-    pub owner: Signer<'info>,
+    pub admin: Signer<'info>,
     #[account(
         mut,
         seeds = [CONFIG_SEED],
         bump = config.bumps.config
     )]
     pub config: Box<Account<'info, Config>>,
-    #[account(
-        seeds = [LEVEL_CONFIG_SEED],
-        bump = level_config.bump,
-        constraint = level_config.admin == config.admin,
-        constraint = level_config.mind_mint == config.mind_mint
-    )]
-    pub level_config: Box<Account<'info, LevelConfig>>,
-    #[account(
-        mut,
-        constraint = mind_mint.key() == config.mind_mint
-    )]
-    pub mind_mint: Account<'info, Mint>,
     #[account(
         mut,
-        seeds = [PROFILE_SEED, owner.key().as_ref()],
-        bump
+        seeds = [TREASURY_VAULT_SEED],
+        bump,
+        constraint = treasury_vault.key() == config.treasury_vault
     )]
-    /// CHECK: PDA derived from PROFILE_SEED; validated in instruction handlers.
-    pub user_profile: UncheckedAccount<'info>,
+    pub treasury_vault: Account<'info, NativeVault>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SweepTreasury<'info> {
     #[account(
-        mut,
-        constraint = owner_mind_ata.owner == owner.key(),
-        constraint = owner_mind_ata.mint == config.mind_mint
+        seeds = [CONFIG_SEED],
+        bump = config.bumps.config
     )]
-    pub owner_mind_ata: Account<'info, TokenAccount>,
+    pub config: Box<Account<'info, Config>>,
     #[account(
         mut,
-        constraint = burn_mind_vault.key() == level_config.mind_burn_vault,
-        constraint = burn_mind_vault.mint == config.mind_mint
+        seeds = [TREASURY_VAULT_SEED],
+        bump,
+        constraint = treasury_vault.key() == config.treasury_vault
     )]
-    pub burn_mind_vault: Account<'info, TokenAccount>,
+    pub treasury_vault: Account<'info, NativeVault>,
     #[account(
         mut,
-        constraint = treasury_mind_vault.key() == level_config.mind_treasury_vault,
-        constraint = treasury_mind_vault.mint == config.mind_mint
+        constraint = treasury_destination.key() == config.treasury_destination
     )]
-    pub treasury_mind_vault: Account<'info, TokenAccount>,
-    pub token_program: Program<'info, Token>,
-    pub system_program: Program<'info, System>,
+    /// CHECK: Only receives lamports; must match `config.treasury_destination`.
+    pub treasury_destination: UncheckedAccount<'info>,
 }
 
 #[derive(Accounts)]
-pub struct SyncProfile<'info> {
+pub struct AdminSetBadge<'info> {
     #[account(mut)]
-    pub owner: Signer<'info>,
+    pub admin: Signer<'info>,
     #[account(
+        mut,
         seeds = [CONFIG_SEED],
         bump = config.bumps.config
     )]
     pub config: Box<Account<'info, Config>>,
+    /// CHECK: used only for PDA derivation
+    pub user: UncheckedAccount<'info>,
     #[account(
         mut,
-        seeds = [PROFILE_SEED, owner.key().as_ref()],
+        seeds = [PROFILE_SEED, user.key().as_ref()],
         bump
     )]
     /// CHECK: PDA derived from PROFILE_SEED; validated in instruction handlers.
@@ -2040,9 +4248,9 @@ pub struct SyncProfile<'info> {
 }
 
 #[derive(Accounts)]
-pub struct StakeMind<'info> {
+pub struct AdminAddXp<'info> {
     #[account(mut)]
-    pub owner: Signer<'info>,
+    pub admin: Signer<'info>,
     #[account(
         mut,
         seeds = [CONFIG_SEED],
@@ -2051,180 +4259,221 @@ pub struct StakeMind<'info> {
     pub config: Box<Account<'info, Config>>,
     #[account(
         mut,
-        seeds = [PROFILE_SEED, owner.key().as_ref()],
+        seeds = [PROFILE_SEED, admin.key().as_ref()],
         bump
     )]
     /// CHECK: PDA derived from PROFILE_SEED; validated in instruction handlers.
     pub user_profile: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct GetUserSummary<'info> {
+    /// CHECK: used only for PDA derivation
+    pub owner: UncheckedAccount<'info>,
     #[account(
-        init_if_needed,
-        payer = owner,
-        space = 8 + UserStake::INIT_SPACE,
-        seeds = [STAKE_SEED, owner.key().as_ref()],
-        bump
+        seeds = [CONFIG_SEED],
+        bump = config.bumps.config
     )]
-    pub user_stake: Box<Account<'info, UserStake>>,
-    #[account(seeds = [VAULT_SEED], bump = config.bumps.vault_authority)]
-    /// CHECK: PDA derived from VAULT_SEED/bump used as vault authority.
-    pub vault_authority: UncheckedAccount<'info>,
+    pub config: Box<Account<'info, Config>>,
     #[account(
-        mut,
-        constraint = staking_mind_vault.key() == config.staking_mind_vault,
-        constraint = staking_mind_vault.owner == vault_authority.key(),
-        constraint = staking_mind_vault.mint == config.mind_mint
+        seeds = [PROFILE_SEED, owner.key().as_ref()],
+        bump
     )]
-    pub staking_mind_vault: Account<'info, TokenAccount>,
+    /// CHECK: PDA derived from PROFILE_SEED; may not exist yet.
+    pub user_profile: UncheckedAccount<'info>,
     #[account(
-        mut,
-        constraint = owner_mind_ata.owner == owner.key(),
-        constraint = owner_mind_ata.mint == config.mind_mint
+        seeds = [STAKE_SEED, owner.key().as_ref()],
+        bump
     )]
-    pub owner_mind_ata: Account<'info, TokenAccount>,
-    pub token_program: Program<'info, Token>,
-    pub system_program: Program<'info, System>,
+    /// CHECK: PDA derived from STAKE_SEED; may not exist yet.
+    pub user_stake: UncheckedAccount<'info>,
 }
 
 #[derive(Accounts)]
-pub struct UnstakeMind<'info> {
-    #[account(mut)]
-    pub owner: Signer<'info>,
+pub struct GetVaultBalances<'info> {
     #[account(
-        mut,
         seeds = [CONFIG_SEED],
         bump = config.bumps.config
     )]
     pub config: Box<Account<'info, Config>>,
     #[account(
-        mut,
-        seeds = [STAKE_SEED, owner.key().as_ref()],
-        bump = user_stake.bump,
-        constraint = user_stake.owner == owner.key()
+        seeds = [STAKING_REWARD_VAULT_SEED],
+        bump,
+        constraint = staking_reward_vault.key() == config.staking_reward_vault
     )]
-    pub user_stake: Box<Account<'info, UserStake>>,
-    #[account(seeds = [VAULT_SEED], bump = config.bumps.vault_authority)]
-    /// CHECK: PDA derived from VAULT_SEED/bump used as vault authority.
-    pub vault_authority: UncheckedAccount<'info>,
+    pub staking_reward_vault: Account<'info, NativeVault>,
     #[account(
-        mut,
-        constraint = staking_mind_vault.key() == config.staking_mind_vault,
-        constraint = staking_mind_vault.owner == vault_authority.key(),
-        constraint = staking_mind_vault.mint == config.mind_mint
+        seeds = [TREASURY_VAULT_SEED],
+        bump,
+        constraint = treasury_vault.key() == config.treasury_vault
     )]
-    pub staking_mind_vault: Account<'info, TokenAccount>,
+    pub treasury_vault: Account<'info, NativeVault>,
+}
+
+#[derive(Accounts)]
+pub struct ReservePositionIndex<'info> {
+    /// CHECK: used only for PDA derivation
+    pub owner: UncheckedAccount<'info>,
     #[account(
-        mut,
-        constraint = config.mind_mint == mind_mint.key()
+        seeds = [PROFILE_SEED, owner.key().as_ref()],
+        bump
     )]
-    pub mind_mint: Account<'info, Mint>,
+    /// CHECK: PDA derived from PROFILE_SEED; may not exist yet.
+    pub user_profile: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct GetContractTerms<'info> {
     #[account(
-        mut,
-        constraint = owner_mind_ata.owner == owner.key(),
-        constraint = owner_mind_ata.mint == config.mind_mint
+        seeds = [CONFIG_SEED],
+        bump = config.bumps.config
     )]
-    pub owner_mind_ata: Account<'info, TokenAccount>,
-    pub token_program: Program<'info, Token>,
+    pub config: Box<Account<'info, Config>>,
 }
 
 #[derive(Accounts)]
-pub struct ClaimXnt<'info> {
-    #[account(mut)]
-    pub owner: Signer<'info>,
+pub struct QuoteBuyContract<'info> {
+    /// CHECK: used only for PDA derivation
+    pub owner: UncheckedAccount<'info>,
     #[account(
-        mut,
         seeds = [CONFIG_SEED],
         bump = config.bumps.config
     )]
     pub config: Box<Account<'info, Config>>,
     #[account(
-        mut,
         seeds = [PROFILE_SEED, owner.key().as_ref()],
         bump
     )]
-    /// CHECK: PDA derived from PROFILE_SEED; validated in instruction handlers.
+    /// CHECK: PDA derived from PROFILE_SEED; may not exist yet.
     pub user_profile: UncheckedAccount<'info>,
+    #[account(seeds = [LEVEL_BONUS_TABLE_SEED], bump)]
+    /// CHECK: PDA derived from LEVEL_BONUS_TABLE_SEED; read by `load_level_bonus_table`.
+    pub level_bonus_table: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct GetFleetBuffQuotes<'info> {
+    /// CHECK: used only to match position owners
+    pub owner: UncheckedAccount<'info>,
     #[account(
-        mut,
-        seeds = [STAKE_SEED, owner.key().as_ref()],
-        bump = user_stake.bump,
-        constraint = user_stake.owner == owner.key()
-    )]
-    pub user_stake: Box<Account<'info, UserStake>>,
-    #[account(
-        mut,
-        seeds = [STAKING_REWARD_VAULT_SEED],
-        bump,
-        constraint = staking_reward_vault.key() == config.staking_reward_vault
+        seeds = [CONFIG_SEED],
+        bump = config.bumps.config
     )]
-    pub staking_reward_vault: Account<'info, NativeVault>,
-    pub system_program: Program<'info, System>,
+    pub config: Box<Account<'info, Config>>,
 }
 
 #[derive(Accounts)]
-pub struct RollEpoch<'info> {
-    pub admin: Signer<'info>,
+pub struct SimulateClaimAll<'info> {
+    /// CHECK: used only for PDA derivation
+    pub owner: UncheckedAccount<'info>,
     #[account(
-        mut,
         seeds = [CONFIG_SEED],
         bump = config.bumps.config
     )]
     pub config: Box<Account<'info, Config>>,
     #[account(
-        mut,
-        seeds = [STAKING_REWARD_VAULT_SEED],
-        bump,
-        constraint = staking_reward_vault.key() == config.staking_reward_vault
+        seeds = [PROFILE_SEED, owner.key().as_ref()],
+        bump
     )]
-    pub staking_reward_vault: Account<'info, NativeVault>,
+    /// CHECK: PDA derived from PROFILE_SEED; validated in instruction handlers.
+    pub user_profile: UncheckedAccount<'info>,
+    #[account(seeds = [LEVEL_BONUS_TABLE_SEED], bump)]
+    /// CHECK: PDA derived from LEVEL_BONUS_TABLE_SEED; read by `load_level_bonus_table`.
+    pub level_bonus_table: Option<UncheckedAccount<'info>>,
 }
 
 #[derive(Accounts)]
-pub struct AdminUpdateConfig<'info> {
+pub struct ProbePositions<'info> {
+    /// CHECK: used only for PDA derivation
+    pub owner: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AdminProposeTransfer<'info> {
     #[account(mut)]
     pub admin: Signer<'info>,
     #[account(
-        mut,
         seeds = [CONFIG_SEED],
         bump = config.bumps.config
     )]
     pub config: Box<Account<'info, Config>>,
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + PendingAdmin::INIT_SPACE,
+        seeds = [PENDING_ADMIN_SEED],
+        bump
+    )]
+    pub pending_admin: Account<'info, PendingAdmin>,
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct AdminFixAccumulator<'info> {
+pub struct AdminAcceptTransfer<'info> {
     #[account(mut)]
-    pub admin: Signer<'info>,
+    pub new_admin: Signer<'info>,
     #[account(
         mut,
         seeds = [CONFIG_SEED],
-        bump = config.bumps.config,
-        has_one = admin
+        bump = config.bumps.config
     )]
     pub config: Box<Account<'info, Config>>,
     #[account(
         mut,
-        seeds = [PROFILE_SEED, user_profile.owner.as_ref()],
-        bump = user_profile.bump
+        seeds = [PENDING_ADMIN_SEED],
+        bump = pending_admin.bump,
+        close = new_admin
     )]
-    pub user_profile: Box<Account<'info, UserMiningProfile>>,
-    pub system_program: Program<'info, System>,
+    pub pending_admin: Account<'info, PendingAdmin>,
 }
 
 #[derive(Accounts)]
-pub struct AdminSetNetworkHpActive<'info> {
+pub struct AdminCancelTransfer<'info> {
     #[account(mut)]
     pub admin: Signer<'info>,
     #[account(
-        mut,
         seeds = [CONFIG_SEED],
         bump = config.bumps.config
     )]
     pub config: Box<Account<'info, Config>>,
+    #[account(
+        mut,
+        seeds = [PENDING_ADMIN_SEED],
+        bump = pending_admin.bump,
+        close = admin
+    )]
+    pub pending_admin: Account<'info, PendingAdmin>,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct PendingAdmin {
+    pub candidate: Pubkey,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct DelegateRecord {
+    pub owner: Pubkey,
+    pub delegate: Pubkey,
+    /// Bitfield of `DELEGATE_PERMISSION_*` flags.
+    pub permissions: u8,
+    pub bump: u8,
+}
+
+/// Per-level HP bonus in bps, indexed by level; levels past the end use the last entry.
+#[account]
+#[derive(InitSpace)]
+pub struct LevelBonusTable {
+    pub bonuses: [u16; LEVEL_BONUS_LEVELS],
+    pub bump: u8,
 }
 
 #[derive(Accounts)]
-pub struct AdminEnableHpScaling<'info> {
-    #[account(mut)]
-    pub admin: Signer<'info>,
+pub struct CrankEpoch<'info> {
+    pub caller: Signer<'info>,
     #[account(
         mut,
         seeds = [CONFIG_SEED],
@@ -2232,18 +4481,16 @@ pub struct AdminEnableHpScaling<'info> {
     )]
     pub config: Box<Account<'info, Config>>,
     #[account(
-        init_if_needed,
-        payer = admin,
-        space = 8 + HpScaleConfig::INIT_SPACE,
-        seeds = [HP_SCALE_SEED],
-        bump
+        mut,
+        seeds = [STAKING_REWARD_VAULT_SEED],
+        bump,
+        constraint = staking_reward_vault.key() == config.staking_reward_vault
     )]
-    pub hp_scale_config: Box<Account<'info, HpScaleConfig>>,
-    pub system_program: Program<'info, System>,
+    pub staking_reward_vault: Account<'info, NativeVault>,
 }
 
 #[derive(Accounts)]
-pub struct AdminUseNativeXnt<'info> {
+pub struct AdminInitLevelBonusTable<'info> {
     #[account(mut)]
     pub admin: Signer<'info>,
     #[account(
@@ -2253,105 +4500,99 @@ pub struct AdminUseNativeXnt<'info> {
     )]
     pub config: Box<Account<'info, Config>>,
     #[account(
-        init_if_needed,
-        payer = admin,
-        space = 8 + NativeVault::INIT_SPACE,
-        seeds = [STAKING_REWARD_VAULT_SEED],
-        bump
-    )]
-    pub staking_reward_vault: Account<'info, NativeVault>,
-    #[account(
-        init_if_needed,
+        init,
         payer = admin,
-        space = 8 + NativeVault::INIT_SPACE,
-        seeds = [TREASURY_VAULT_SEED],
+        space = 8 + LevelBonusTable::INIT_SPACE,
+        seeds = [LEVEL_BONUS_TABLE_SEED],
         bump
     )]
-    pub treasury_vault: Account<'info, NativeVault>,
+    pub level_bonus_table: Account<'info, LevelBonusTable>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct AdminSetMetadata<'info> {
-    #[account(mut)]
+pub struct AdminUpdateLevelBonusTable<'info> {
     pub admin: Signer<'info>,
     #[account(
         seeds = [CONFIG_SEED],
         bump = config.bumps.config
     )]
     pub config: Box<Account<'info, Config>>,
-    #[account(seeds = [VAULT_SEED], bump = config.bumps.vault_authority)]
-    /// CHECK: PDA used as mint authority signer for metadata creation.
-    pub vault_authority: UncheckedAccount<'info>,
     #[account(
-        constraint = mind_mint.key() == config.mind_mint
+        mut,
+        seeds = [LEVEL_BONUS_TABLE_SEED],
+        bump = level_bonus_table.bump
     )]
-    pub mind_mint: Account<'info, Mint>,
-    #[account(mut)]
-    /// CHECK: Metaplex metadata PDA for the MIND mint.
-    pub metadata: UncheckedAccount<'info>,
-    #[account(address = METADATA_PROGRAM_ID)]
-    /// CHECK: Metaplex token metadata program.
-    pub metadata_program: UncheckedAccount<'info>,
-    pub system_program: Program<'info, System>,
-    pub rent: Sysvar<'info, Rent>,
+    pub level_bonus_table: Account<'info, LevelBonusTable>,
 }
 
 #[derive(Accounts)]
-pub struct AdminWithdrawStakingRewards<'info> {
+#[instruction(position_index: u64)]
+pub struct ClosePosition<'info> {
     #[account(mut)]
-    pub admin: Signer<'info>,
+    pub owner: Signer<'info>,
     #[account(
-        mut,
         seeds = [CONFIG_SEED],
         bump = config.bumps.config
     )]
     pub config: Box<Account<'info, Config>>,
     #[account(
         mut,
-        seeds = [STAKING_REWARD_VAULT_SEED],
-        bump = staking_reward_vault.bump,
-        constraint = staking_reward_vault.key() == config.staking_reward_vault
+        seeds = [POSITION_SEED, owner.key().as_ref(), position_index.to_le_bytes().as_ref()],
+        bump
     )]
-    pub staking_reward_vault: Account<'info, NativeVault>,
-    pub system_program: Program<'info, System>,
+    /// CHECK: Decoded with `load_position_any` so legacy layouts can be closed too.
+    pub position: UncheckedAccount<'info>,
 }
 
 #[derive(Accounts)]
-pub struct AdminWithdrawTreasury<'info> {
+#[instruction(delegate: Pubkey)]
+pub struct SetDelegate<'info> {
     #[account(mut)]
-    pub admin: Signer<'info>,
+    pub owner: Signer<'info>,
     #[account(
-        mut,
-        seeds = [CONFIG_SEED],
-        bump = config.bumps.config
+        init_if_needed,
+        payer = owner,
+        space = 8 + DelegateRecord::INIT_SPACE,
+        seeds = [DELEGATE_SEED, owner.key().as_ref(), delegate.as_ref()],
+        bump
     )]
-    pub config: Box<Account<'info, Config>>,
+    pub delegate_record: Account<'info, DelegateRecord>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeDelegate<'info> {
+    /// Either the owner or the delegate.
+    pub authority: Signer<'info>,
+    #[account(mut, address = delegate_record.owner)]
+    /// CHECK: Receives the record's rent; pinned to `delegate_record.owner`.
+    pub owner: UncheckedAccount<'info>,
     #[account(
         mut,
-        seeds = [TREASURY_VAULT_SEED],
-        bump,
-        constraint = treasury_vault.key() == config.treasury_vault
+        close = owner,
+        seeds = [
+            DELEGATE_SEED,
+            delegate_record.owner.as_ref(),
+            delegate_record.delegate.as_ref()
+        ],
+        bump = delegate_record.bump
     )]
-    pub treasury_vault: Account<'info, NativeVault>,
-    pub system_program: Program<'info, System>,
+    pub delegate_record: Account<'info, DelegateRecord>,
 }
 
 #[derive(Accounts)]
-pub struct AdminSetBadge<'info> {
+pub struct ClaimBadge<'info> {
     #[account(mut)]
-    pub admin: Signer<'info>,
+    pub owner: Signer<'info>,
     #[account(
-        mut,
         seeds = [CONFIG_SEED],
         bump = config.bumps.config
     )]
     pub config: Box<Account<'info, Config>>,
-    /// CHECK: used only for PDA derivation
-    pub user: UncheckedAccount<'info>,
     #[account(
         mut,
-        seeds = [PROFILE_SEED, user.key().as_ref()],
+        seeds = [PROFILE_SEED, owner.key().as_ref()],
         bump
     )]
     /// CHECK: PDA derived from PROFILE_SEED; validated in instruction handlers.
@@ -2360,9 +4601,9 @@ pub struct AdminSetBadge<'info> {
 }
 
 #[derive(Accounts)]
-pub struct AdminAddXp<'info> {
+pub struct CrankDeactivate<'info> {
     #[account(mut)]
-    pub admin: Signer<'info>,
+    pub caller: Signer<'info>,
     #[account(
         mut,
         seeds = [CONFIG_SEED],
@@ -2371,12 +4612,37 @@ pub struct AdminAddXp<'info> {
     pub config: Box<Account<'info, Config>>,
     #[account(
         mut,
-        seeds = [PROFILE_SEED, admin.key().as_ref()],
+        seeds = [TREASURY_VAULT_SEED],
+        bump,
+        constraint = treasury_vault.key() == config.treasury_vault
+    )]
+    pub treasury_vault: Account<'info, NativeVault>,
+    pub system_program: Program<'info, System>,
+    #[account(seeds = [LEVEL_BONUS_TABLE_SEED], bump)]
+    /// CHECK: PDA derived from LEVEL_BONUS_TABLE_SEED; read by `load_level_bonus_table`.
+    pub level_bonus_table: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct SimulateClaimMind<'info> {
+    /// CHECK: used only for PDA derivation
+    pub owner: UncheckedAccount<'info>,
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bumps.config
+    )]
+    pub config: Box<Account<'info, Config>>,
+    #[account(
+        seeds = [PROFILE_SEED, owner.key().as_ref()],
         bump
     )]
     /// CHECK: PDA derived from PROFILE_SEED; validated in instruction handlers.
     pub user_profile: UncheckedAccount<'info>,
-    pub system_program: Program<'info, System>,
+    /// CHECK: Manual position decoding supports legacy sizes.
+    pub position: UncheckedAccount<'info>,
+    #[account(seeds = [LEVEL_BONUS_TABLE_SEED], bump)]
+    /// CHECK: PDA derived from LEVEL_BONUS_TABLE_SEED; read by `load_level_bonus_table`.
+    pub level_bonus_table: Option<UncheckedAccount<'info>>,
 }
 
 #[account]
@@ -2446,6 +4712,52 @@ pub struct Config {
     pub staking_undistributed_xnt: u64,
     pub staking_accounted_balance: u64,
     pub bumps: ConfigBumps,
+    pub auto_roll_epoch_seconds: u64,
+    pub forfeit_expired_rewards: bool,
+    pub forfeit_window_seconds: u64,
+    pub unstake_cooldown_sec: u64,
+    pub treasury_sweep_threshold: u64,
+    pub treasury_destination: Pubkey,
+    pub staking_xnt_vault: Pubkey,
+    pub max_consecutive_renews: u16,
+    pub staker_emission_bps: u16,
+    pub staking_acc_mind_per_mind: u128,
+    pub epoch_rollers: [Pubkey; MAX_EPOCH_ROLLERS],
+    pub max_mint_per_claim: u64,
+    pub staking_paused: bool,
+    pub reward_round_half_up: bool,
+    pub paused: bool,
+    pub epoch_seq: u64,
+    pub staking_lock_days: u64,
+    pub badge_merkle_root: [u8; 32],
+    pub crank_reward: u64,
+    pub min_position_hp: u64,
+    pub staking_share_bps: u16,
+    pub level_bonus_table_initialized: bool,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct ConfigV1Legacy {
+    pub admin: Pubkey,
+    pub emission_per_sec: u64,
+    pub acc_mind_per_hp: u128,
+    pub last_update_ts: i64,
+    pub network_hp_active: u64,
+    pub mind_mint: Pubkey,
+    pub xnt_mint: Pubkey,
+    pub staking_reward_vault: Pubkey,
+    pub treasury_vault: Pubkey,
+    pub staking_mind_vault: Pubkey,
+    pub max_effective_hp: u64,
+    pub seconds_per_day: u64,
+    pub staking_acc_xnt_per_mind: u128,
+    pub staking_last_update_ts: i64,
+    pub staking_reward_rate_xnt_per_sec: u64,
+    pub staking_epoch_end_ts: i64,
+    pub staking_total_staked_mind: u64,
+    pub staking_undistributed_xnt: u64,
+    pub staking_accounted_balance: u64,
+    pub bumps: ConfigBumps,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
@@ -2473,6 +4785,17 @@ pub struct RigBuffConfig {
     pub mind_treasury_vault: Pubkey,
     pub mind_per_hp_per_day: u64,
     pub bump: u8,
+    pub min_buff_upgrade_interval_sec: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct RigBuffConfigV1Legacy {
+    pub admin: Pubkey,
+    pub mind_mint: Pubkey,
+    pub mind_burn_vault: Pubkey,
+    pub mind_treasury_vault: Pubkey,
+    pub mind_per_hp_per_day: u64,
+    pub bump: u8,
 }
 
 #[account]
@@ -2498,6 +4821,25 @@ pub struct UserMiningProfile {
     pub last_xp_update_ts: i64,
     pub hp_scaled: bool,
     pub level_acc_snapshots: [u128; 7],
+    /// Level bonus `network_hp_active` counts for this profile's HP; follows the table on sync.
+    pub level_bonus_bps: u16,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct UserMiningProfileV4Legacy {
+    pub owner: Pubkey,
+    pub next_position_index: u64,
+    pub active_hp: u64,
+    pub buffed_hp: u64,
+    pub buffed_hp_synced: bool,
+    pub xp: u64,
+    pub badge_tier: u8,
+    pub badge_bonus_bps: u16,
+    pub bump: u8,
+    pub level: u8,
+    pub last_xp_update_ts: i64,
+    pub hp_scaled: bool,
+    pub level_acc_snapshots: [u128; 7],
 }
 
 #[account]
@@ -2517,6 +4859,10 @@ pub struct MinerPosition {
     pub expired: bool,
     pub buff_applied_from_cycle: u64,
     pub last_level_applied: u8,
+    pub consecutive_renews: u16,
+    pub last_buff_upgrade_ts: i64,
+    /// Level bonus `reward_debt` was settled with; frozen once the position expires.
+    pub level_bonus_bps: u16,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
@@ -2531,6 +4877,24 @@ pub struct MinerPositionV1 {
     pub bump: u8,
 }
 
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct MinerPositionV3Legacy {
+    pub owner: Pubkey,
+    pub hp: u64,
+    pub start_ts: i64,
+    pub end_ts: i64,
+    pub reward_debt: u128,
+    pub final_acc_mind_per_hp: u128,
+    pub deactivated: bool,
+    pub bump: u8,
+    pub rig_type: u8,
+    pub buff_level: u8,
+    pub hp_scaled: bool,
+    pub expired: bool,
+    pub buff_applied_from_cycle: u64,
+    pub last_level_applied: u8,
+}
+
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
 pub struct MinerPositionV2Legacy {
     pub owner: Pubkey,
@@ -2556,6 +4920,22 @@ pub struct UserStake {
     pub reward_debt: u128,
     pub reward_owed: u64,
     pub bump: u8,
+    pub mind_reward_debt: u128,
+    pub mind_reward_owed: u64,
+    /// Stake-weighted average deposit time; the lock runs from here.
+    pub stake_start_ts: i64,
+    /// MIND waiting out the unstake cooldown, net of the burn.
+    pub pending_unstake: u64,
+    pub unstake_available_ts: i64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct UserStakeV1Legacy {
+    pub owner: Pubkey,
+    pub staked_mind: u64,
+    pub reward_debt: u128,
+    pub reward_owed: u64,
+    pub bump: u8,
 }
 
 #[event]
@@ -2586,39 +4966,310 @@ pub struct MindStaked {
 }
 
 #[event]
-pub struct MindUnstaked {
-    pub owner: Pubkey,
-    pub amount: u64,
+pub struct MindUnstaked {
+    pub owner: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+    pub struct XntClaimed {
+        pub owner: Pubkey,
+        pub amount: u64,
+        pub bonus_bps: u16,
+    }
+
+    #[event]
+    pub struct StakingRewardsWithdrawn {
+        pub admin: Pubkey,
+        pub amount: u64,
+    }
+
+#[event]
+pub struct EpochRolled {
+    pub rate: u64,
+    pub epoch_end_ts: i64,
+    pub epoch_seq: u64,
+}
+
+#[event]
+pub struct UserSummary {
+    pub owner: Pubkey,
+    pub level: u8,
+    pub xp: u64,
+    pub active_hp: u64,
+    pub badge_tier: u8,
+    pub badge_bonus_bps: u16,
+    pub staked_mind: u64,
+    pub pending_xnt: u64,
+}
+
+#[event]
+pub struct VaultBalances {
+    pub treasury_lamports: u64,
+    pub staking_reward_lamports: u64,
+    pub staking_accounted_balance: u64,
+    pub staking_undistributed_xnt: u64,
+}
+
+#[event]
+pub struct StakedMindClaimed {
+    pub owner: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct XntClaimedPartial {
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub remaining_owed: u64,
+}
+
+#[event]
+pub struct StakingVaultShortfall {
+    pub vault_balance: u64,
+    pub shortfall: u64,
+    pub trimmed_undistributed_xnt: u64,
+}
+
+#[event]
+pub struct NextPositionIndex {
+    pub owner: Pubkey,
+    pub next_position_index: u64,
+    pub position: Pubkey,
+}
+
+#[event]
+pub struct ExpiredRewardsForfeited {
+    pub owner: Pubkey,
+    pub position: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct PositionRescaled {
+    pub owner: Pubkey,
+    pub position: Pubkey,
+    pub hp_scaled: u64,
+    pub final_acc_mind_per_hp: u128,
+}
+
+#[event]
+pub struct PositionRepaired {
+    pub admin: Pubkey,
+    pub position: Pubkey,
+    pub owner: Pubkey,
+    pub old_reward_debt: u128,
+    pub new_reward_debt: u128,
+    pub old_final_acc_mind_per_hp: u128,
+    pub new_final_acc_mind_per_hp: u128,
+    pub old_expired: bool,
+    pub new_expired: bool,
+    pub old_deactivated: bool,
+    pub new_deactivated: bool,
+    pub old_buff_level: u8,
+    pub new_buff_level: u8,
+    pub old_last_level_applied: u8,
+    pub new_last_level_applied: u8,
+}
+
+#[event]
+pub struct UnstakeRequested {
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub pending_unstake: u64,
+    pub unstake_available_ts: i64,
+}
+
+#[event]
+pub struct ContractTerms {
+    pub contract_type: u8,
+    pub duration_days: u64,
+    pub base_hp_scaled: u64,
+    pub cost_base: u64,
+}
+
+#[event]
+pub struct BuyQuote {
+    pub owner: Pubkey,
+    pub contract_type: u8,
+    pub duration_days: u64,
+    pub base_hp_scaled: u64,
+    pub cost_base: u64,
+    pub staking_share: u64,
+    pub treasury_share: u64,
+    pub hp_effective: u64,
+    pub network_share_bps: u16,
+}
+
+#[event]
+pub struct TreasurySwept {
+    pub destination: Pubkey,
+    pub amount: u64,
+    pub remaining: u64,
+}
+
+#[event]
+pub struct FleetBuffQuote {
+    pub owner: Pubkey,
+    pub position: Pubkey,
+    pub rig_type: u8,
+    pub buff_level: u8,
+    pub next_buff_level: u8,
+    pub current_buff_bps: u16,
+    pub next_buff_bps: u16,
+    pub renew_cost_base: u64,
+    pub fleet_buff_bps_after: u16,
+    pub exceeds_cap: bool,
+}
+
+#[event]
+pub struct ConfigMigrated {
+    pub admin: Pubkey,
+    pub new_size: u64,
+}
+
+#[event]
+pub struct PositionExistence {
+    pub owner: Pubkey,
+    pub start_index: u64,
+    pub bitmap: u64,
+    pub active_bitmap: u64,
+}
+
+#[event]
+pub struct PositionExpired {
+    pub owner: Pubkey,
+    pub position: Pubkey,
+    pub final_acc_mind_per_hp: u128,
+    pub hp_effective_removed: u64,
+}
+
+#[event]
+pub struct SimulatedClaim {
+    pub owner: Pubkey,
+    pub position: Pubkey,
+    pub pending: u64,
+    pub expired: bool,
+    pub hp_effective: u64,
+}
+
+#[event]
+pub struct SimulatedClaimTotal {
+    pub owner: Pubkey,
+    pub positions: u32,
+    pub total_pending: u64,
+}
+
+#[event]
+pub struct BatchMindClaimed {
+    pub owner: Pubkey,
+    pub total_amount: u64,
+    pub position_count: u8,
+}
+
+#[event]
+pub struct AdminTransferProposed {
+    pub admin: Pubkey,
+    pub candidate: Pubkey,
+}
+
+#[event]
+pub struct AdminTransferred {
+    pub old_admin: Pubkey,
+    pub new_admin: Pubkey,
+}
+
+#[event]
+pub struct ProtocolPauseChanged {
+    pub paused: bool,
+    pub ts: i64,
+}
+
+#[event]
+pub struct LevelBonusTableUpdated {
+    pub admin: Pubkey,
+    pub bonuses: [u16; LEVEL_BONUS_LEVELS],
+}
+
+#[event]
+pub struct PositionClosed {
+    pub owner: Pubkey,
+    pub position_index: u64,
+}
+
+#[event]
+pub struct DelegateSet {
+    pub owner: Pubkey,
+    pub delegate: Pubkey,
+    pub permissions: u8,
+}
+
+#[event]
+pub struct DelegateRevoked {
+    pub owner: Pubkey,
+    pub delegate: Pubkey,
+}
+
+#[event]
+pub struct BadgeClaimed {
+    pub owner: Pubkey,
+    pub badge_tier: u8,
+    pub badge_bonus_bps: u16,
+}
+
+#[event]
+pub struct CrankDeactivated {
+    pub count: u8,
 }
 
 #[event]
-    pub struct XntClaimed {
-        pub owner: Pubkey,
-        pub amount: u64,
-        pub bonus_bps: u16,
-    }
+pub struct StakingShareUpdated {
+    pub old_bps: u16,
+    pub new_bps: u16,
+}
 
-    #[event]
-    pub struct StakingRewardsWithdrawn {
-        pub admin: Pubkey,
-        pub amount: u64,
-    }
+fn level_bonus_bps(level: u8, bonus_table: Option<&LevelBonusTable>) -> u16 {
+    let bonuses = bonus_table.map_or(&DEFAULT_LEVEL_BONUSES, |table| &table.bonuses);
+    bonuses[(level as usize).min(LEVEL_BONUS_LEVELS - 1)]
+}
 
-    #[event]
-    pub struct EpochRolled {
-        pub rate: u64,
-        pub epoch_end_ts: i64,
+fn level_bonus_for(level: u8, bonus_table: Option<&LevelBonusTable>) -> u16 {
+    let level_for_bonus = if LEVELING_ENABLED { level } else { 1 };
+    level_bonus_bps(level_for_bonus, bonus_table)
 }
 
-fn level_bonus_bps(level: u8) -> u16 {
-    match level {
-        0 | 1 => 0,
-        2 => 160,
-        3 => 340,
-        4 => 550,
-        5 => 780,
-        _ => LEVEL_BONUS_CAP_BPS,
-    }
+/// Reads the level bonus table PDA. Until `admin_init_level_bonus_table` creates it the
+/// account may be omitted and the built-in bonuses apply; after that it is required.
+fn load_level_bonus_table(
+    info: Option<&AccountInfo>,
+    cfg: &Config,
+) -> Result<Option<LevelBonusTable>> {
+    let info = match info {
+        Some(info) if !info.data_is_empty() => info,
+        _ => {
+            require!(
+                !cfg.level_bonus_table_initialized,
+                ErrorCode::LevelBonusTableRequired
+            );
+            return Ok(None);
+        }
+    };
+    require!(info.owner == &crate::ID, ErrorCode::InvalidLevelBonusTable);
+    let data = info.try_borrow_data()?;
+    let mut slice: &[u8] = &data;
+    Ok(Some(LevelBonusTable::try_deserialize(&mut slice)?))
+}
+
+fn validate_level_bonuses(bonuses: &[u16; LEVEL_BONUS_LEVELS]) -> Result<()> {
+    require!(
+        bonuses.iter().all(|&bps| bps <= LEVEL_BONUS_CAP_BPS),
+        ErrorCode::InvalidLevelBonus
+    );
+    require!(
+        bonuses.windows(2).all(|pair| pair[0] <= pair[1]),
+        ErrorCode::InvalidLevelBonus
+    );
+    Ok(())
 }
 
 fn level_threshold(level: u8) -> u64 {
@@ -2661,6 +5312,15 @@ fn position_base_hp_scaled(position: &PositionData) -> Result<u128> {
     }
 }
 
+/// Same bounds as `admin_update_config`; `localnet` builds accept any non-zero day so
+/// tests can run with seconds-long days.
+fn seconds_per_day_allowed(seconds_per_day: u64) -> bool {
+    if cfg!(feature = "localnet") {
+        return seconds_per_day > 0;
+    }
+    (SECONDS_PER_DAY_MIN_ALLOWED..=SECONDS_PER_DAY_MAX_ALLOWED).contains(&seconds_per_day)
+}
+
 fn rig_type_from_duration(start_ts: i64, end_ts: i64, seconds_per_day: u64) -> Result<u8> {
     require!(end_ts > start_ts, ErrorCode::InvalidRigDuration);
     let duration = end_ts
@@ -2729,13 +5389,42 @@ fn position_buff_bps(position: &PositionData, rig_type: u8, now: i64) -> u16 {
     }
 }
 
-fn effective_hp_scaled(base_hp_scaled: u128, level: u8, rig_buff_bps: u16) -> Result<u128> {
+fn effective_hp_scaled(
+    base_hp_scaled: u128,
+    level: u8,
+    rig_buff_bps: u16,
+    bonus_table: Option<&LevelBonusTable>,
+) -> Result<u128> {
+    effective_hp_with_bonus(
+        base_hp_scaled,
+        rig_buff_bps,
+        level_bonus_for(level, bonus_table),
+    )
+}
+
+fn effective_hp_with_bonus(
+    base_hp_scaled: u128,
+    rig_buff_bps: u16,
+    level_bonus: u16,
+) -> Result<u128> {
     let with_rig_buff = apply_bps(base_hp_scaled, rig_buff_bps)?;
-    let level_for_bonus = if LEVELING_ENABLED { level } else { 1 };
-    let level_bonus = level_bonus_bps(level_for_bonus);
     apply_bps(with_rig_buff, level_bonus)
 }
 
+/// Level bonus the position's `reward_debt` was settled with. Positions written before the
+/// bonus was recorded use the built-in bonus of the level they were last settled at.
+fn position_level_bonus_bps(position: &PositionData, profile_level: u8) -> u16 {
+    if position.level_bonus_bps != LEVEL_BONUS_UNRECORDED {
+        return position.level_bonus_bps;
+    }
+    let level = if position.expired && position.last_level_applied == 0 {
+        profile_level
+    } else {
+        position.last_level_applied.max(1)
+    };
+    level_bonus_for(level, None)
+}
+
 fn effective_hp_for_claim(
     position: &PositionData,
     profile_level: u8,
@@ -2756,16 +5445,8 @@ fn effective_hp_for_claim(
     let rig_type = position_rig_type(position, cfg)?;
     let base_hp_scaled = position_base_hp_scaled(position)?;
     let buff_bps = position_buff_bps(position, rig_type, now);
-    let level_for_calc = if position.expired {
-        if position.last_level_applied == 0 {
-            profile_level
-        } else {
-            position.last_level_applied
-        }
-    } else {
-        profile_level
-    };
-    let hp_effective = effective_hp_scaled(base_hp_scaled, level_for_calc, buff_bps)?;
+    let level_bonus = position_level_bonus_bps(position, profile_level);
+    let hp_effective = effective_hp_with_bonus(base_hp_scaled, buff_bps, level_bonus)?;
     let acc = if position.expired {
         position.final_acc_mind_per_hp
     } else {
@@ -2833,6 +5514,7 @@ fn init_profile_defaults(profile: &mut UserMiningProfile, owner: Pubkey, bump: u
         profile.last_xp_update_ts = now;
         profile.hp_scaled = true;
         profile.level_acc_snapshots = [0; 7];
+        profile.level_bonus_bps = level_bonus_for(1, None);
         return;
     }
     if profile.level == 0 {
@@ -2873,16 +5555,16 @@ fn increase_profile_hp(
     base_hp_scaled: u128,
     buffed_hp_scaled: u128,
 ) -> Result<()> {
-    let base_u64 = u64::try_from(base_hp_scaled).map_err(|_| ErrorCode::MathOverflow)?;
-    let buffed_u64 = u64::try_from(buffed_hp_scaled).map_err(|_| ErrorCode::MathOverflow)?;
+    let base_u64 = u64::try_from(base_hp_scaled).map_err(|_| ErrorCode::HpMathOverflow)?;
+    let buffed_u64 = u64::try_from(buffed_hp_scaled).map_err(|_| ErrorCode::HpMathOverflow)?;
     profile.active_hp = profile
         .active_hp
         .checked_add(base_u64)
-        .ok_or(ErrorCode::MathOverflow)?;
+        .ok_or(ErrorCode::HpMathOverflow)?;
     profile.buffed_hp = profile
         .buffed_hp
         .checked_add(buffed_u64)
-        .ok_or(ErrorCode::MathOverflow)?;
+        .ok_or(ErrorCode::HpMathOverflow)?;
     Ok(())
 }
 
@@ -2891,16 +5573,16 @@ fn decrease_profile_hp(
     base_hp_scaled: u128,
     buffed_hp_scaled: u128,
 ) -> Result<()> {
-    let base_u64 = u64::try_from(base_hp_scaled).map_err(|_| ErrorCode::MathOverflow)?;
-    let buffed_u64 = u64::try_from(buffed_hp_scaled).map_err(|_| ErrorCode::MathOverflow)?;
+    let base_u64 = u64::try_from(base_hp_scaled).map_err(|_| ErrorCode::HpMathOverflow)?;
+    let buffed_u64 = u64::try_from(buffed_hp_scaled).map_err(|_| ErrorCode::HpMathOverflow)?;
     profile.active_hp = profile
         .active_hp
         .checked_sub(base_u64)
-        .ok_or(ErrorCode::MathOverflow)?;
+        .ok_or(ErrorCode::HpMathOverflow)?;
     profile.buffed_hp = profile
         .buffed_hp
         .checked_sub(buffed_u64)
-        .ok_or(ErrorCode::MathOverflow)?;
+        .ok_or(ErrorCode::HpMathOverflow)?;
     Ok(())
 }
 
@@ -2916,6 +5598,28 @@ fn load_user_profile_any(info: &AccountInfo) -> Result<UserMiningProfile> {
         let mut slice: &[u8] = &data;
         return UserMiningProfile::try_deserialize(&mut slice);
     }
+    // Layouts without `level_bonus_bps` were last settled with the built-in bonuses.
+    if data.len() == 8 + UserMiningProfileV4Legacy::INIT_SPACE {
+        let mut slice: &[u8] = &data[8..];
+        let legacy = UserMiningProfileV4Legacy::deserialize(&mut slice)
+            .map_err(|_| ErrorCode::InvalidUserProfileSize)?;
+        return Ok(UserMiningProfile {
+            owner: legacy.owner,
+            next_position_index: legacy.next_position_index,
+            active_hp: legacy.active_hp,
+            buffed_hp: legacy.buffed_hp,
+            buffed_hp_synced: legacy.buffed_hp_synced,
+            xp: legacy.xp,
+            badge_tier: legacy.badge_tier,
+            badge_bonus_bps: legacy.badge_bonus_bps,
+            bump: legacy.bump,
+            level: legacy.level,
+            last_xp_update_ts: legacy.last_xp_update_ts,
+            hp_scaled: legacy.hp_scaled,
+            level_acc_snapshots: legacy.level_acc_snapshots,
+            level_bonus_bps: level_bonus_for(legacy.level, None),
+        });
+    }
     if data.len() == 8 + UserMiningProfileV3Legacy::INIT_SPACE {
         let mut slice: &[u8] = &data[8..];
         let legacy = UserMiningProfileV3Legacy::deserialize(&mut slice)
@@ -2934,6 +5638,7 @@ fn load_user_profile_any(info: &AccountInfo) -> Result<UserMiningProfile> {
             last_xp_update_ts: legacy.last_xp_update_ts,
             hp_scaled: legacy.hp_scaled,
             level_acc_snapshots: [0; 7],
+            level_bonus_bps: level_bonus_for(legacy.level, None),
         });
     }
     if data.len() == 8 + UserMiningProfileV2Legacy::INIT_SPACE {
@@ -2954,6 +5659,7 @@ fn load_user_profile_any(info: &AccountInfo) -> Result<UserMiningProfile> {
             last_xp_update_ts: legacy.last_xp_update_ts,
             hp_scaled: false,
             level_acc_snapshots: [0; 7],
+            level_bonus_bps: level_bonus_for(legacy.level, None),
         });
     }
     if data.len() == 8 + UserMiningProfileV1::INIT_SPACE {
@@ -2974,6 +5680,7 @@ fn load_user_profile_any(info: &AccountInfo) -> Result<UserMiningProfile> {
             last_xp_update_ts: 0,
             hp_scaled: false,
             level_acc_snapshots: [0; 7],
+            level_bonus_bps: level_bonus_for(0, None),
         });
     }
     Err(ErrorCode::InvalidUserProfileSize.into())
@@ -2987,7 +5694,28 @@ fn save_user_profile(info: &AccountInfo, profile: &UserMiningProfile) -> Result<
         profile.try_serialize(&mut cursor)?;
         return Ok(());
     }
-    if len == 8 + UserMiningProfileV3Legacy::INIT_SPACE {
+    if len == 8 + UserMiningProfileV4Legacy::INIT_SPACE {
+        let legacy = UserMiningProfileV4Legacy {
+            owner: profile.owner,
+            next_position_index: profile.next_position_index,
+            active_hp: profile.active_hp,
+            buffed_hp: profile.buffed_hp,
+            buffed_hp_synced: profile.buffed_hp_synced,
+            xp: profile.xp,
+            badge_tier: profile.badge_tier,
+            badge_bonus_bps: profile.badge_bonus_bps,
+            bump: profile.bump,
+            level: profile.level,
+            last_xp_update_ts: profile.last_xp_update_ts,
+            hp_scaled: profile.hp_scaled,
+            level_acc_snapshots: profile.level_acc_snapshots,
+        };
+        data[..8].copy_from_slice(&UserMiningProfile::DISCRIMINATOR);
+        let mut cursor: &mut [u8] = &mut data[8..];
+        legacy
+            .serialize(&mut cursor)
+            .map_err(|_| ErrorCode::InvalidUserProfileSize.into())
+    } else if len == 8 + UserMiningProfileV3Legacy::INIT_SPACE {
         let legacy = UserMiningProfileV3Legacy {
             owner: profile.owner,
             next_position_index: profile.next_position_index,
@@ -3042,6 +5770,14 @@ fn save_user_profile(info: &AccountInfo, profile: &UserMiningProfile) -> Result<
     }
 }
 
+fn load_user_stake(info: &AccountInfo) -> Result<UserStake> {
+    require!(info.owner == &crate::ID, ErrorCode::InvalidUserStake);
+    let data = info.try_borrow_data()?;
+    let mut slice: &[u8] = &data;
+    UserStake::try_deserialize(&mut slice)
+}
+
+#[derive(Clone)]
 struct PositionData {
     owner: Pubkey,
     hp: u64,
@@ -3057,10 +5793,40 @@ struct PositionData {
     expired: bool,
     buff_applied_from_cycle: u64,
     last_level_applied: u8,
+    consecutive_renews: u16,
+    last_buff_upgrade_ts: i64,
+    level_bonus_bps: u16,
     version: u8,
 }
 
 fn load_position_any(info: &AccountInfo) -> Result<PositionData> {
+    let mut position = load_position_unscaled(info)?;
+    rescale_legacy_expired(&mut position)?;
+    Ok(position)
+}
+
+/// Converts a position that expired before `admin_enable_hp_scaling` to the scaled units:
+/// its `final_acc_mind_per_hp` was frozen unscaled, so `hp` is scaled up and the accumulator
+/// down, keeping `hp * acc` unchanged. Later expiries set `hp_scaled`, so they are left as is.
+/// Returns whether the position was converted.
+fn rescale_legacy_expired(position: &mut PositionData) -> Result<bool> {
+    if position.hp_scaled || !position.expired || position.deactivated {
+        return Ok(false);
+    }
+    position.hp = position
+        .hp
+        .checked_mul(HP_SCALE_U64)
+        .ok_or(ErrorCode::HpMathOverflow)?;
+    position.final_acc_mind_per_hp = position
+        .final_acc_mind_per_hp
+        .checked_div(HP_SCALE)
+        .ok_or(ErrorCode::HpMathOverflow)?;
+    position.hp_scaled = true;
+    Ok(true)
+}
+
+/// `load_position_any` without the legacy expiry rescale.
+fn load_position_unscaled(info: &AccountInfo) -> Result<PositionData> {
     require!(info.owner == &crate::ID, ErrorCode::InvalidPositionOwner);
     let data = info.try_borrow_data()?;
     require!(data.len() >= 8, ErrorCode::InvalidPositionSize);
@@ -3068,10 +5834,11 @@ fn load_position_any(info: &AccountInfo) -> Result<PositionData> {
         data[..8] == MinerPosition::DISCRIMINATOR,
         ErrorCode::InvalidPositionDiscriminator
     );
-    let v3_size = 8 + MinerPosition::INIT_SPACE;
+    let v4_size = 8 + MinerPosition::INIT_SPACE;
+    let v3_size = 8 + MinerPositionV3Legacy::INIT_SPACE;
     let v2_size = 8 + MinerPositionV2Legacy::INIT_SPACE;
     let v1_size = 8 + MinerPositionV1::INIT_SPACE;
-    if data.len() >= v3_size {
+    if data.len() >= v4_size {
         let mut slice: &[u8] = &data;
         let position = MinerPosition::try_deserialize(&mut slice)
             .map_err(|_| ErrorCode::InvalidPositionSize)?;
@@ -3090,6 +5857,34 @@ fn load_position_any(info: &AccountInfo) -> Result<PositionData> {
             expired: position.expired,
             buff_applied_from_cycle: position.buff_applied_from_cycle,
             last_level_applied: position.last_level_applied,
+            consecutive_renews: position.consecutive_renews,
+            last_buff_upgrade_ts: position.last_buff_upgrade_ts,
+            level_bonus_bps: position.level_bonus_bps,
+            version: 4,
+        });
+    }
+    if data.len() >= v3_size {
+        let mut slice: &[u8] = &data[8..];
+        let position = MinerPositionV3Legacy::deserialize(&mut slice)
+            .map_err(|_| ErrorCode::InvalidPositionSize)?;
+        return Ok(PositionData {
+            owner: position.owner,
+            hp: position.hp,
+            start_ts: position.start_ts,
+            end_ts: position.end_ts,
+            reward_debt: position.reward_debt,
+            final_acc_mind_per_hp: position.final_acc_mind_per_hp,
+            deactivated: position.deactivated,
+            bump: position.bump,
+            rig_type: position.rig_type,
+            buff_level: position.buff_level,
+            hp_scaled: position.hp_scaled,
+            expired: position.expired,
+            buff_applied_from_cycle: position.buff_applied_from_cycle,
+            last_level_applied: position.last_level_applied,
+            consecutive_renews: 0,
+            last_buff_upgrade_ts: 0,
+            level_bonus_bps: LEVEL_BONUS_UNRECORDED,
             version: 3,
         });
     }
@@ -3112,6 +5907,9 @@ fn load_position_any(info: &AccountInfo) -> Result<PositionData> {
             expired: position.expired,
             buff_applied_from_cycle: position.buff_applied_from_cycle,
             last_level_applied: 0,
+            consecutive_renews: 0,
+            last_buff_upgrade_ts: 0,
+            level_bonus_bps: LEVEL_BONUS_UNRECORDED,
             version: 2,
         });
     }
@@ -3134,6 +5932,9 @@ fn load_position_any(info: &AccountInfo) -> Result<PositionData> {
             expired: false,
             buff_applied_from_cycle: 0,
             last_level_applied: 0,
+            consecutive_renews: 0,
+            last_buff_upgrade_ts: 0,
+            level_bonus_bps: LEVEL_BONUS_UNRECORDED,
             version: 1,
         });
     }
@@ -3142,10 +5943,11 @@ fn load_position_any(info: &AccountInfo) -> Result<PositionData> {
 
 fn save_position(info: &AccountInfo, position: &PositionData) -> Result<()> {
     let mut data = info.try_borrow_mut_data()?;
-    let v3_size = 8 + MinerPosition::INIT_SPACE;
+    let v4_size = 8 + MinerPosition::INIT_SPACE;
+    let v3_size = 8 + MinerPositionV3Legacy::INIT_SPACE;
     let v2_size = 8 + MinerPositionV2Legacy::INIT_SPACE;
     let v1_size = 8 + MinerPositionV1::INIT_SPACE;
-    if data.len() >= v3_size {
+    if data.len() >= v4_size {
         let upgraded = MinerPosition {
             owner: position.owner,
             hp: position.hp,
@@ -3161,11 +5963,37 @@ fn save_position(info: &AccountInfo, position: &PositionData) -> Result<()> {
             expired: position.expired,
             buff_applied_from_cycle: position.buff_applied_from_cycle,
             last_level_applied: position.last_level_applied,
+            consecutive_renews: position.consecutive_renews,
+            last_buff_upgrade_ts: position.last_buff_upgrade_ts,
+            level_bonus_bps: position.level_bonus_bps,
         };
         let mut cursor: &mut [u8] = &mut data;
         upgraded.try_serialize(&mut cursor)?;
         return Ok(());
     }
+    if data.len() == v3_size {
+        let legacy = MinerPositionV3Legacy {
+            owner: position.owner,
+            hp: position.hp,
+            start_ts: position.start_ts,
+            end_ts: position.end_ts,
+            reward_debt: position.reward_debt,
+            final_acc_mind_per_hp: position.final_acc_mind_per_hp,
+            deactivated: position.deactivated,
+            bump: position.bump,
+            rig_type: position.rig_type,
+            buff_level: position.buff_level,
+            hp_scaled: position.hp_scaled,
+            expired: position.expired,
+            buff_applied_from_cycle: position.buff_applied_from_cycle,
+            last_level_applied: position.last_level_applied,
+        };
+        data[..8].copy_from_slice(&MinerPosition::DISCRIMINATOR);
+        let mut cursor: &mut [u8] = &mut data[8..];
+        return legacy
+            .serialize(&mut cursor)
+            .map_err(|_| ErrorCode::InvalidPositionSize.into());
+    }
     if data.len() == v2_size {
         let legacy = MinerPositionV2Legacy {
             owner: position.owner,
@@ -3218,6 +6046,18 @@ fn ensure_position_v2<'info>(
         return Ok(());
     }
     require!(info.owner == &crate::ID, ErrorCode::InvalidPositionOwner);
+    resize_program_account(info, payer, system_program, new_size)
+}
+
+fn resize_program_account<'info>(
+    info: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    new_size: usize,
+) -> Result<()> {
+    if info.data_len() >= new_size {
+        return Ok(());
+    }
     let needed = Rent::get()?.minimum_balance(new_size);
     let current = info.lamports();
     if needed > current {
@@ -3237,6 +6077,44 @@ fn ensure_position_v2<'info>(
     Ok(())
 }
 
+/// Manual counterpart of `close = ...` for accounts decoded by hand, whose older layouts
+/// a typed `Account` cannot load.
+fn close_program_account<'info>(
+    info: &AccountInfo<'info>,
+    destination: &AccountInfo<'info>,
+) -> Result<()> {
+    transfer_lamports(info, destination, info.lamports())?;
+    info.try_borrow_mut_data()?.fill(0);
+    info.assign(&system_program::ID);
+    info.realloc(0, false)?;
+    Ok(())
+}
+
+fn load_rig_buff_config_any(info: &AccountInfo) -> Result<RigBuffConfig> {
+    require!(info.owner == &crate::ID, ErrorCode::InvalidRigBuffConfig);
+    let data = info.try_borrow_data()?;
+    require!(
+        data.len() >= 8 && data[..8] == RigBuffConfig::DISCRIMINATOR,
+        ErrorCode::InvalidRigBuffConfig
+    );
+    if data.len() >= 8 + RigBuffConfig::INIT_SPACE {
+        let mut slice: &[u8] = &data;
+        return RigBuffConfig::try_deserialize(&mut slice);
+    }
+    let mut slice: &[u8] = &data[8..];
+    let legacy = RigBuffConfigV1Legacy::deserialize(&mut slice)
+        .map_err(|_| ErrorCode::InvalidRigBuffConfig)?;
+    Ok(RigBuffConfig {
+        admin: legacy.admin,
+        mind_mint: legacy.mind_mint,
+        mind_burn_vault: legacy.mind_burn_vault,
+        mind_treasury_vault: legacy.mind_treasury_vault,
+        mind_per_hp_per_day: legacy.mind_per_hp_per_day,
+        bump: legacy.bump,
+        min_buff_upgrade_interval_sec: 0,
+    })
+}
+
 fn ensure_user_profile_v2<'info>(
     info: &AccountInfo<'info>,
     payer: &AccountInfo<'info>,
@@ -3279,6 +6157,7 @@ fn ensure_user_profile_v2<'info>(
             last_xp_update_ts: now,
             hp_scaled: true,
             level_acc_snapshots: [0; 7],
+            level_bonus_bps: level_bonus_for(1, None),
         };
         save_user_profile(info, &profile)?;
         return Ok(profile);
@@ -3365,47 +6244,101 @@ fn contract_terms(contract_type: u8) -> Result<(u64, u64, u64)> {
     }
 }
 
-fn earned_per_hp(hp: u128, acc_mind_per_hp: u128) -> Result<u128> {
-    hp.checked_mul(acc_mind_per_hp)
+fn split_contract_cost(cost_base: u64, staking_share_bps: u16) -> Result<(u64, u64)> {
+    let staking_share = (cost_base as u128)
+        .checked_mul(staking_share_bps as u128)
         .ok_or(ErrorCode::MathOverflow)?
+        .checked_div(BPS_DENOMINATOR)
+        .ok_or(ErrorCode::MathOverflow)? as u64;
+    let treasury_share = cost_base
+        .checked_sub(staking_share)
+        .ok_or(ErrorCode::MathOverflow)?;
+    Ok((staking_share, treasury_share))
+}
+
+fn earned_per_hp(hp: u128, acc_mind_per_hp: u128) -> Result<u128> {
+    earned_per_hp_rounded(hp, acc_mind_per_hp, false)
+}
+
+fn earned_per_hp_rounded(hp: u128, acc_mind_per_hp: u128, round_half_up: bool) -> Result<u128> {
+    let product = hp
+        .checked_mul(acc_mind_per_hp)
+        .ok_or(ErrorCode::MathOverflow)?;
+    let product = if round_half_up {
+        product
+            .checked_add(ACC_SCALE / 2)
+            .ok_or(ErrorCode::MathOverflow)?
+    } else {
+        product
+    };
+    product
         .checked_div(ACC_SCALE)
         .ok_or(ErrorCode::MathOverflow.into())
 }
 
-fn update_mining_global(cfg: &mut Account<Config>, now: i64) -> Result<()> {
+fn update_mining_global(cfg: &mut Config, now: i64) -> Result<()> {
     if now <= cfg.last_update_ts {
         return Ok(());
     }
+    // Nothing is emitted while paused; the clock just moves with it.
+    if cfg.paused {
+        cfg.last_update_ts = now;
+        return Ok(());
+    }
     let dt = now
         .checked_sub(cfg.last_update_ts)
-        .ok_or(ErrorCode::MathOverflow)?;
-    let dt_u64 = u64::try_from(dt).map_err(|_| ErrorCode::MathOverflow)?;
+        .ok_or(ErrorCode::EmissionMathOverflow)?;
+    let dt_u64 = u64::try_from(dt).map_err(|_| ErrorCode::EmissionMathOverflow)?;
     require!(dt_u64 <= MAX_MINING_DT_SECONDS, ErrorCode::UpdateWindowExceeded);
+    let mintable = (dt as u128)
+        .checked_mul(cfg.emission_per_sec as u128)
+        .ok_or(ErrorCode::EmissionMathOverflow)?;
+    // The staker share only applies while MIND is staked; otherwise miners keep it all.
+    let staker_mintable = if cfg.staking_total_staked_mind > 0 {
+        mintable
+            .checked_mul(cfg.staker_emission_bps as u128)
+            .ok_or(ErrorCode::EmissionMathOverflow)?
+            .checked_div(BPS_DENOMINATOR)
+            .ok_or(ErrorCode::EmissionMathOverflow)?
+    } else {
+        0
+    };
+    if staker_mintable > 0 {
+        let staker_delta = staker_mintable
+            .checked_mul(ACC_SCALE)
+            .ok_or(ErrorCode::EmissionMathOverflow)?
+            .checked_div(cfg.staking_total_staked_mind as u128)
+            .ok_or(ErrorCode::EmissionMathOverflow)?;
+        cfg.staking_acc_mind_per_mind = cfg
+            .staking_acc_mind_per_mind
+            .checked_add(staker_delta)
+            .ok_or(ErrorCode::EmissionMathOverflow)?;
+    }
     if cfg.network_hp_active == 0 {
         cfg.last_update_ts = now;
         return Ok(());
     }
-    let mintable = (dt as u128)
-        .checked_mul(cfg.emission_per_sec as u128)
-        .ok_or(ErrorCode::MathOverflow)?;
-    let delta = mintable
+    let miner_mintable = mintable
+        .checked_sub(staker_mintable)
+        .ok_or(ErrorCode::EmissionMathOverflow)?;
+    let delta = miner_mintable
         .checked_mul(ACC_SCALE)
-        .ok_or(ErrorCode::MathOverflow)?
+        .ok_or(ErrorCode::EmissionMathOverflow)?
         .checked_div(cfg.network_hp_active as u128)
-        .ok_or(ErrorCode::MathOverflow)?;
+        .ok_or(ErrorCode::EmissionMathOverflow)?;
     if cfg.acc_mind_per_hp > 0 {
         let acc_cap = cfg
             .acc_mind_per_hp
             .checked_mul(MINING_ACC_DELTA_CAP_BPS as u128)
-            .ok_or(ErrorCode::MathOverflow)?
+            .ok_or(ErrorCode::EmissionMathOverflow)?
             .checked_div(BPS_DENOMINATOR)
-            .ok_or(ErrorCode::MathOverflow)?;
+            .ok_or(ErrorCode::EmissionMathOverflow)?;
         require!(delta <= acc_cap, ErrorCode::AccDeltaTooLarge);
     }
     cfg.acc_mind_per_hp = cfg
         .acc_mind_per_hp
         .checked_add(delta)
-        .ok_or(ErrorCode::MathOverflow)?;
+        .ok_or(ErrorCode::EmissionMathOverflow)?;
     cfg.last_update_ts = now;
     Ok(())
 }
@@ -3413,10 +6346,10 @@ fn update_mining_global(cfg: &mut Account<Config>, now: i64) -> Result<()> {
 fn grace_deadline_ts(end_ts: i64, seconds_per_day: u64) -> Result<i64> {
     let grace = (seconds_per_day as i64)
         .checked_mul(2)
-        .ok_or(ErrorCode::MathOverflow)?;
+        .ok_or(ErrorCode::HpMathOverflow)?;
     end_ts
         .checked_add(grace)
-        .ok_or(ErrorCode::MathOverflow.into())
+        .ok_or(ErrorCode::HpMathOverflow.into())
 }
 
 fn renew_window_start_ts(end_ts: i64, seconds_per_day: u64) -> Result<i64> {
@@ -3429,9 +6362,8 @@ fn renew_window_start_ts(end_ts: i64, seconds_per_day: u64) -> Result<i64> {
 }
 
 fn apply_pending_buff(
-    cfg: &mut Account<Config>,
+    cfg: &mut Config,
     position: &mut PositionData,
-    profile_level: u8,
     now: i64,
     profile: &mut UserMiningProfile,
 ) -> Result<()> {
@@ -3466,8 +6398,8 @@ fn apply_pending_buff(
     if effective_ts > cfg.last_update_ts {
         update_mining_global(cfg, effective_ts)?;
     }
-    let hp_prev = effective_hp_scaled(base_hp_scaled, profile_level, prev_bps)?;
-    let hp_new = effective_hp_scaled(base_hp_scaled, profile_level, new_bps)?;
+    let hp_prev = effective_hp_with_bonus(base_hp_scaled, prev_bps, profile.level_bonus_bps)?;
+    let hp_new = effective_hp_with_bonus(base_hp_scaled, new_bps, profile.level_bonus_bps)?;
     let buffed_prev = apply_bps(base_hp_scaled, prev_bps)?;
     let buffed_new = apply_bps(base_hp_scaled, new_bps)?;
     if hp_new > hp_prev {
@@ -3493,56 +6425,295 @@ fn apply_pending_buff(
     Ok(())
 }
 
-fn apply_level_updates_to_position(
-    position: &mut PositionData,
-    profile: &UserMiningProfile,
-    rig_type: u8,
-    now: i64,
-) -> Result<()> {
-    if position.deactivated || position.expired {
+/// Moves `reward_debt` onto the profile's level and level bonus. Level-ups apply from their
+/// `level_acc_snapshots` entry; a bonus table change since the last settle applies from
+/// `acc_mind_per_hp`, so it never reaches back into rewards already accrued.
+fn apply_level_updates_to_position(
+    position: &mut PositionData,
+    profile: &UserMiningProfile,
+    rig_type: u8,
+    now: i64,
+    acc_mind_per_hp: u128,
+    bonus_table: Option<&LevelBonusTable>,
+) -> Result<()> {
+    if position.deactivated || position.expired {
+        return Ok(());
+    }
+    let settled_bonus = position_level_bonus_bps(position, profile.level);
+    if position.last_level_applied >= profile.level && settled_bonus == profile.level_bonus_bps {
+        position.level_bonus_bps = settled_bonus;
+        return Ok(());
+    }
+    let base_hp_scaled = position_base_hp_scaled(position)?;
+    // Avoid over-adjusting level deltas while a future buff is pending.
+    let buff_bps = if position.buff_applied_from_cycle != 0 {
+        let prev_level = position.buff_level.saturating_sub(1);
+        rig_buff_bps(rig_type, prev_level)
+    } else {
+        position_buff_bps(position, rig_type, now)
+    };
+    let bonus_at = |level: u8| {
+        if level >= profile.level {
+            profile.level_bonus_bps
+        } else {
+            level_bonus_for(level, bonus_table)
+        }
+    };
+    let mut prev_level = position.last_level_applied.max(1);
+    let mut reward_debt = shift_reward_debt(
+        position.reward_debt,
+        effective_hp_with_bonus(base_hp_scaled, buff_bps, settled_bonus)?,
+        effective_hp_with_bonus(base_hp_scaled, buff_bps, bonus_at(prev_level))?,
+        acc_mind_per_hp,
+    )?;
+    while prev_level < profile.level {
+        let next_level = prev_level
+            .checked_add(1)
+            .ok_or(ErrorCode::MathOverflow)?;
+        let hp_prev = effective_hp_with_bonus(base_hp_scaled, buff_bps, bonus_at(prev_level))?;
+        let hp_new = effective_hp_with_bonus(base_hp_scaled, buff_bps, bonus_at(next_level))?;
+        let snap = profile.level_acc_snapshots[next_level as usize];
+        reward_debt = shift_reward_debt(reward_debt, hp_prev, hp_new, snap)?;
+        prev_level = next_level;
+    }
+    position.reward_debt = reward_debt;
+    position.last_level_applied = position.last_level_applied.max(profile.level);
+    position.level_bonus_bps = profile.level_bonus_bps;
+    Ok(())
+}
+
+/// Rebases `reward_debt` from `hp_from` to `hp_to` so pending MIND is unchanged at
+/// `acc_mind_per_hp`.
+fn shift_reward_debt(
+    reward_debt: u128,
+    hp_from: u128,
+    hp_to: u128,
+    acc_mind_per_hp: u128,
+) -> Result<u128> {
+    if hp_to >= hp_from {
+        let delta = earned_per_hp(hp_to - hp_from, acc_mind_per_hp)?;
+        reward_debt
+            .checked_add(delta)
+            .ok_or(ErrorCode::MathOverflow.into())
+    } else {
+        let delta = earned_per_hp(hp_from - hp_to, acc_mind_per_hp)?;
+        Ok(reward_debt.saturating_sub(delta))
+    }
+}
+
+/// Moves the profile, and `network_hp_active` with it, onto the bonus the table now gives
+/// its level. Positions follow as they are settled; until then each keeps earning at the
+/// bonus its `reward_debt` was settled with.
+fn sync_profile_level_bonus(
+    cfg: &mut Config,
+    profile: &mut UserMiningProfile,
+    now: i64,
+    bonus_table: Option<&LevelBonusTable>,
+) -> Result<()> {
+    let live_bonus = level_bonus_for(profile.level, bonus_table);
+    if live_bonus == profile.level_bonus_bps {
+        return Ok(());
+    }
+    update_mining_global(cfg, now)?;
+    let hp_total = if profile.buffed_hp_synced {
+        profile.buffed_hp
+    } else {
+        profile.active_hp
+    } as u128;
+    let effective_before = apply_bps(hp_total, profile.level_bonus_bps)?;
+    let effective_after = apply_bps(hp_total, live_bonus)?;
+    let network = cfg.network_hp_active as u128;
+    let network = if effective_after >= effective_before {
+        network
+            .checked_add(effective_after - effective_before)
+            .ok_or(ErrorCode::MathOverflow)?
+    } else {
+        network.saturating_sub(effective_before - effective_after)
+    };
+    cfg.network_hp_active = u64::try_from(network).map_err(|_| ErrorCode::MathOverflow)?;
+    profile.level_bonus_bps = live_bonus;
+    Ok(())
+}
+
+/// Blends a new deposit into the stake's start time so topping up cannot reset or dodge
+/// the lock: `(old_staked * old_ts + amount * now) / new_total`. Stake that has already
+/// served the lock counts as starting `lock_seconds` ago, so an old (or migrated, zero) start
+/// cannot pull a fresh deposit out of the lock.
+fn weighted_stake_start_ts(
+    user_stake: &UserStake,
+    amount: u64,
+    now: i64,
+    lock_seconds: i64,
+) -> Result<i64> {
+    if user_stake.staked_mind == 0 {
+        return Ok(now);
+    }
+    let old_start = if lock_seconds > 0 {
+        let unlocked_start = now
+            .checked_sub(lock_seconds)
+            .ok_or(ErrorCode::StakingMathOverflow)?;
+        user_stake.stake_start_ts.max(unlocked_start)
+    } else {
+        user_stake.stake_start_ts
+    };
+    let old_weight = (user_stake.staked_mind as i128)
+        .checked_mul(old_start as i128)
+        .ok_or(ErrorCode::StakingMathOverflow)?;
+    let new_weight = (amount as i128)
+        .checked_mul(now as i128)
+        .ok_or(ErrorCode::StakingMathOverflow)?;
+    let total = (user_stake.staked_mind as i128)
+        .checked_add(amount as i128)
+        .ok_or(ErrorCode::StakingMathOverflow)?;
+    let ts = old_weight
+        .checked_add(new_weight)
+        .ok_or(ErrorCode::StakingMathOverflow)?
+        .checked_div(total)
+        .ok_or(ErrorCode::StakingMathOverflow)?;
+    i64::try_from(ts).map_err(|_| ErrorCode::StakingMathOverflow.into())
+}
+
+fn stake_lock_seconds(cfg: &Config) -> Result<i64> {
+    let lock_seconds = cfg
+        .staking_lock_days
+        .checked_mul(cfg.seconds_per_day)
+        .ok_or(ErrorCode::StakingMathOverflow)?;
+    i64::try_from(lock_seconds).map_err(|_| ErrorCode::StakingMathOverflow.into())
+}
+
+fn check_stake_lock(cfg: &Config, user_stake: &UserStake, now: i64) -> Result<()> {
+    if cfg.staking_lock_days == 0 {
         return Ok(());
     }
-    if position.last_level_applied >= profile.level {
-        return Ok(());
+    let unlock_ts = user_stake
+        .stake_start_ts
+        .checked_add(stake_lock_seconds(cfg)?)
+        .ok_or(ErrorCode::StakingMathOverflow)?;
+    require!(now >= unlock_ts, ErrorCode::StakeLockActive);
+    Ok(())
+}
+
+/// Account that authorizes, and pays for, an owner action: the owner when it signed,
+/// otherwise a delegate whose record grants `permission`.
+fn owner_or_delegate<'info>(
+    owner: &AccountInfo<'info>,
+    delegate: Option<&Signer<'info>>,
+    delegate_record: Option<&DelegateRecord>,
+    permission: u8,
+) -> Result<AccountInfo<'info>> {
+    if owner.is_signer {
+        return Ok(owner.clone());
     }
-    let base_hp_scaled = position_base_hp_scaled(position)?;
-    // Avoid over-adjusting level deltas while a future buff is pending.
-    let buff_bps = if position.buff_applied_from_cycle != 0 {
-        let prev_level = position.buff_level.saturating_sub(1);
-        rig_buff_bps(rig_type, prev_level)
-    } else {
-        position_buff_bps(position, rig_type, now)
+    let (delegate, record) = match (delegate, delegate_record) {
+        (Some(delegate), Some(record)) => (delegate, record),
+        _ => return Err(ErrorCode::Unauthorized.into()),
     };
-    let mut prev_level = position.last_level_applied.max(1);
-    while prev_level < profile.level {
-        let next_level = prev_level
-            .checked_add(1)
-            .ok_or(ErrorCode::MathOverflow)?;
-        let hp_prev = effective_hp_scaled(base_hp_scaled, prev_level, buff_bps)?;
-        let hp_new = effective_hp_scaled(base_hp_scaled, next_level, buff_bps)?;
-        let delta_hp = hp_new
-            .checked_sub(hp_prev)
-            .ok_or(ErrorCode::MathOverflow)?;
-        let snap = profile.level_acc_snapshots[next_level as usize];
-        let delta_debt = earned_per_hp(delta_hp, snap)?;
-        position.reward_debt = position
-            .reward_debt
-            .checked_add(delta_debt)
-            .ok_or(ErrorCode::MathOverflow)?;
-        prev_level = next_level;
+    require_keys_eq!(record.owner, owner.key(), ErrorCode::Unauthorized);
+    require_keys_eq!(record.delegate, delegate.key(), ErrorCode::Unauthorized);
+    require!(
+        record.permissions & permission != 0,
+        ErrorCode::DelegatePermissionDenied
+    );
+    Ok(delegate.to_account_info())
+}
+
+fn verify_merkle_proof(proof: &[[u8; 32]], root: [u8; 32], leaf: [u8; 32]) -> bool {
+    let computed = proof.iter().fold(leaf, |node, sibling| {
+        if node <= *sibling {
+            hashv(&[&node, sibling]).to_bytes()
+        } else {
+            hashv(&[sibling, &node]).to_bytes()
+        }
+    });
+    computed == root
+}
+
+/// Runs the settle half of `claim_mind` on scratch copies and returns the pending MIND and
+/// effective HP the claim would use.
+fn simulate_position_claim(
+    cfg: &mut Config,
+    position: &mut PositionData,
+    profile: &mut UserMiningProfile,
+    now: i64,
+    bonus_table: Option<&LevelBonusTable>,
+) -> Result<(u64, u64)> {
+    let rig_type = position_rig_type(position, cfg)?;
+    apply_level_updates_to_position(
+        position,
+        profile,
+        rig_type,
+        now,
+        cfg.acc_mind_per_hp,
+        bonus_table,
+    )?;
+    apply_pending_buff(cfg, position, now, profile)?;
+    if !position.deactivated && !position.expired && now >= position.end_ts {
+        expire_position_state(cfg, position, profile, now)?;
+    } else {
+        update_mining_global(cfg, now)?;
+    }
+    sync_profile_level_bonus(cfg, profile, now, bonus_table)?;
+    apply_level_updates_to_position(
+        position,
+        profile,
+        rig_type,
+        now,
+        cfg.acc_mind_per_hp,
+        bonus_table,
+    )?;
+
+    let (hp_effective, acc_used) = effective_hp_for_claim(position, profile.level, cfg, now)?;
+    let pending = pending_mind(
+        hp_effective,
+        acc_used,
+        position.reward_debt,
+        cfg.reward_round_half_up,
+    )?;
+    Ok((
+        u64::try_from(pending).map_err(|_| ErrorCode::MathOverflow)?,
+        u64::try_from(hp_effective).map_err(|_| ErrorCode::MathOverflow)?,
+    ))
+}
+
+fn check_renew_cap(cfg: &Config, position: &mut PositionData) -> Result<()> {
+    if cfg.max_consecutive_renews > 0 {
+        require!(
+            position.consecutive_renews < cfg.max_consecutive_renews,
+            ErrorCode::RenewCapReached
+        );
     }
-    position.last_level_applied = profile.level;
+    position.consecutive_renews = position.consecutive_renews.saturating_add(1);
     Ok(())
 }
 
 fn expire_position(
     cfg: &mut Account<Config>,
     position: &mut PositionData,
+    position_key: Pubkey,
     user_profile: &mut UserMiningProfile,
     now: i64,
 ) -> Result<()> {
+    if let Some(hp_effective_removed) = expire_position_state(cfg, position, user_profile, now)? {
+        emit!(PositionExpired {
+            owner: position.owner,
+            position: position_key,
+            final_acc_mind_per_hp: position.final_acc_mind_per_hp,
+            hp_effective_removed,
+        });
+    }
+    Ok(())
+}
+
+/// State half of `expire_position`, shared with `simulate_claim_all`. Returns the
+/// effective HP removed from the network, or `None` when the position was not due.
+fn expire_position_state(
+    cfg: &mut Config,
+    position: &mut PositionData,
+    user_profile: &mut UserMiningProfile,
+    now: i64,
+) -> Result<Option<u64>> {
     if position.deactivated || position.expired || now < position.end_ts {
-        return Ok(());
+        return Ok(None);
     }
     if cfg.last_update_ts < position.end_ts {
         update_mining_global(cfg, position.end_ts)?;
@@ -3550,24 +6721,80 @@ fn expire_position(
     let rig_type = position_rig_type(position, cfg)?;
     let base_hp_scaled = position_base_hp_scaled(position)?;
     let buff_bps = position_buff_bps(position, rig_type, now);
-    let hp_effective = effective_hp_scaled(base_hp_scaled, user_profile.level, buff_bps)?;
-    let hp_effective_u64 = u64::try_from(hp_effective).map_err(|_| ErrorCode::MathOverflow)?;
+    let hp_effective =
+        effective_hp_with_bonus(base_hp_scaled, buff_bps, user_profile.level_bonus_bps)?;
+    let hp_effective_u64 = u64::try_from(hp_effective).map_err(|_| ErrorCode::HpMathOverflow)?;
     position.final_acc_mind_per_hp = cfg.acc_mind_per_hp;
     position.expired = true;
     position.last_level_applied = user_profile.level;
+    position.level_bonus_bps = user_profile.level_bonus_bps;
+    if !position.hp_scaled {
+        // `final_acc_mind_per_hp` is already scaled; flag it so reads do not rescale it.
+        position.hp = u64::try_from(base_hp_scaled).map_err(|_| ErrorCode::HpMathOverflow)?;
+        position.hp_scaled = true;
+    }
     cfg.network_hp_active = cfg
         .network_hp_active
         .checked_sub(hp_effective_u64)
-        .ok_or(ErrorCode::MathOverflow)?;
+        .ok_or(ErrorCode::HpMathOverflow)?;
     let buffed_hp_scaled = apply_bps(base_hp_scaled, buff_bps)?;
     decrease_profile_hp(user_profile, base_hp_scaled, buffed_hp_scaled)?;
     update_mining_global(cfg, now)?;
+    Ok(Some(hp_effective_u64))
+}
+
+/// Shared tail of `deactivate_position` and `crank_deactivate`; callers check the grace
+/// deadline and save the accounts.
+fn deactivate_position_state(
+    cfg: &mut Account<Config>,
+    position: &mut PositionData,
+    position_key: Pubkey,
+    profile: &mut UserMiningProfile,
+    now: i64,
+) -> Result<()> {
+    if !position.expired {
+        expire_position(cfg, position, position_key, profile, now)?;
+    } else {
+        update_mining_global(cfg, now)?;
+    }
+
+    finalize_position(cfg, position, position_key, profile, now)?;
+    position.consecutive_renews = 0;
+    if cfg.forfeit_expired_rewards {
+        let forfeit_ts = position
+            .end_ts
+            .checked_add(
+                i64::try_from(cfg.forfeit_window_seconds).map_err(|_| ErrorCode::MathOverflow)?,
+            )
+            .ok_or(ErrorCode::MathOverflow)?;
+        if now >= forfeit_ts {
+            // Unclaimed MIND is never minted; clearing it keeps old positions settled.
+            let (hp_effective, acc_used) =
+                effective_hp_for_claim(position, profile.level, cfg, now)?;
+            let pending = pending_mind(
+                hp_effective,
+                acc_used,
+                position.reward_debt,
+                cfg.reward_round_half_up,
+            )?;
+            if pending > 0 {
+                position.reward_debt =
+                    earned_per_hp_rounded(hp_effective, acc_used, cfg.reward_round_half_up)?;
+                emit!(ExpiredRewardsForfeited {
+                    owner: position.owner,
+                    position: position_key,
+                    amount: u64::try_from(pending).map_err(|_| ErrorCode::MathOverflow)?,
+                });
+            }
+        }
+    }
     Ok(())
 }
 
 fn finalize_position(
     cfg: &mut Account<Config>,
     position: &mut PositionData,
+    position_key: Pubkey,
     user_profile: &mut UserMiningProfile,
     now: i64,
 ) -> Result<()> {
@@ -3577,39 +6804,56 @@ fn finalize_position(
     let rig_type = position_rig_type(position, cfg)?;
     let base_hp_scaled = position_base_hp_scaled(position)?;
     let buff_bps = position_buff_bps(position, rig_type, now);
-    let hp_effective = effective_hp_scaled(base_hp_scaled, user_profile.level, buff_bps)?;
-    let hp_effective_u64 = u64::try_from(hp_effective).map_err(|_| ErrorCode::MathOverflow)?;
+    let level_bonus = if position.expired {
+        position_level_bonus_bps(position, user_profile.level)
+    } else {
+        user_profile.level_bonus_bps
+    };
+    let hp_effective = effective_hp_with_bonus(base_hp_scaled, buff_bps, level_bonus)?;
+    let hp_effective_u64 = u64::try_from(hp_effective).map_err(|_| ErrorCode::HpMathOverflow)?;
     if !position.expired {
         position.final_acc_mind_per_hp = cfg.acc_mind_per_hp;
         cfg.network_hp_active = cfg
             .network_hp_active
             .checked_sub(hp_effective_u64)
-            .ok_or(ErrorCode::MathOverflow)?;
+            .ok_or(ErrorCode::HpMathOverflow)?;
         let buffed_hp_scaled = apply_bps(base_hp_scaled, buff_bps)?;
         decrease_profile_hp(user_profile, base_hp_scaled, buffed_hp_scaled)?;
         position.expired = true;
         position.last_level_applied = user_profile.level;
+        position.level_bonus_bps = level_bonus;
+        emit!(PositionExpired {
+            owner: position.owner,
+            position: position_key,
+            final_acc_mind_per_hp: position.final_acc_mind_per_hp,
+            hp_effective_removed: hp_effective_u64,
+        });
     }
     position.deactivated = true;
     if hp_effective_u64 >= HP_SCALED_MARKER {
-        return Err(ErrorCode::MathOverflow.into());
+        return Err(ErrorCode::HpMathOverflow.into());
     }
     position.hp = hp_effective_u64 | HP_SCALED_MARKER;
     update_mining_global(cfg, now)?;
     Ok(())
 }
 
-fn pending_mind(hp_effective: u128, acc_mind_per_hp: u128, reward_debt: u128) -> Result<u128> {
-    let earned = earned_per_hp(hp_effective, acc_mind_per_hp)?;
+fn pending_mind(
+    hp_effective: u128,
+    acc_mind_per_hp: u128,
+    reward_debt: u128,
+    round_half_up: bool,
+) -> Result<u128> {
+    let earned = earned_per_hp_rounded(hp_effective, acc_mind_per_hp, round_half_up)?;
     Ok(earned.saturating_sub(reward_debt))
 }
 
 fn earned_per_stake(staked: u64, acc_xnt_per_mind: u128) -> Result<u128> {
     (staked as u128)
         .checked_mul(acc_xnt_per_mind)
-        .ok_or(ErrorCode::MathOverflow)?
+        .ok_or(ErrorCode::StakingMathOverflow)?
         .checked_div(ACC_SCALE)
-        .ok_or(ErrorCode::MathOverflow.into())
+        .ok_or(ErrorCode::StakingMathOverflow.into())
 }
 
 fn pending_stake(cfg: &Config, user_stake: &UserStake) -> Result<u128> {
@@ -3622,45 +6866,398 @@ fn accrue_staking_owed(cfg: &Config, user_stake: &mut UserStake) -> Result<()> {
     if pending == 0 {
         return Ok(());
     }
-    let pending_u64 = u64::try_from(pending).map_err(|_| ErrorCode::MathOverflow)?;
+    let pending_u64 = u64::try_from(pending).map_err(|_| ErrorCode::StakingMathOverflow)?;
     user_stake.reward_owed = user_stake
         .reward_owed
         .checked_add(pending_u64)
-        .ok_or(ErrorCode::MathOverflow)?;
+        .ok_or(ErrorCode::StakingMathOverflow)?;
+    Ok(())
+}
+
+fn pending_staked_mind(cfg: &Config, user_stake: &UserStake) -> Result<u128> {
+    let earned = earned_per_stake(user_stake.staked_mind, cfg.staking_acc_mind_per_mind)?;
+    Ok(earned.saturating_sub(user_stake.mind_reward_debt))
+}
+
+fn accrue_staked_mind_owed(cfg: &Config, user_stake: &mut UserStake) -> Result<()> {
+    let pending = pending_staked_mind(cfg, user_stake)?;
+    if pending == 0 {
+        return Ok(());
+    }
+    let pending_u64 = u64::try_from(pending).map_err(|_| ErrorCode::StakingMathOverflow)?;
+    user_stake.mind_reward_owed = user_stake
+        .mind_reward_owed
+        .checked_add(pending_u64)
+        .ok_or(ErrorCode::StakingMathOverflow)?;
+    Ok(())
+}
+
+/// Removes `amount` from the stake and returns `(burn_amount, transfer_amount)`.
+fn remove_stake(cfg: &mut Config, user_stake: &mut UserStake, amount: u64) -> Result<(u64, u64)> {
+    require!(
+        user_stake.staked_mind >= amount,
+        ErrorCode::InsufficientStake
+    );
+
+    accrue_staking_owed(cfg, user_stake)?;
+    accrue_staked_mind_owed(cfg, user_stake)?;
+
+    user_stake.staked_mind = user_stake
+        .staked_mind
+        .checked_sub(amount)
+        .ok_or(ErrorCode::StakingMathOverflow)?;
+    cfg.staking_total_staked_mind = cfg
+        .staking_total_staked_mind
+        .checked_sub(amount)
+        .ok_or(ErrorCode::StakingMathOverflow)?;
+    user_stake.reward_debt =
+        earned_per_stake(user_stake.staked_mind, cfg.staking_acc_xnt_per_mind)?;
+    user_stake.mind_reward_debt =
+        earned_per_stake(user_stake.staked_mind, cfg.staking_acc_mind_per_mind)?;
+
+    let burn_amount = (amount as u128)
+        .checked_mul(UNSTAKE_BURN_BPS)
+        .ok_or(ErrorCode::StakingMathOverflow)?
+        .checked_div(BPS_DENOMINATOR)
+        .ok_or(ErrorCode::StakingMathOverflow)?;
+    let burn_amount = u64::try_from(burn_amount).map_err(|_| ErrorCode::StakingMathOverflow)?;
+    let transfer_amount = amount
+        .checked_sub(burn_amount)
+        .ok_or(ErrorCode::StakingMathOverflow)?;
+    Ok((burn_amount, transfer_amount))
+}
+
+fn is_epoch_roller(cfg: &Config, key: &Pubkey) -> bool {
+    *key != Pubkey::default() && cfg.epoch_rollers.contains(key)
+}
+
+/// The staker MIND accumulator advances inside `update_mining_global`, so stake balance
+/// changes must settle it first. With no staker share there is nothing to settle.
+fn settle_staker_emission(cfg: &mut Account<Config>, now: i64) -> Result<()> {
+    if cfg.staker_emission_bps == 0 {
+        return Ok(());
+    }
+    update_mining_global(cfg, now)
+}
+
+/// In SPL mode `remaining` is `[staking_xnt_vault, owner_xnt_ata, vault_authority, token_program]`.
+fn claim_xnt_payout<'info>(
+    accounts: &mut ClaimXnt<'info>,
+    remaining: &[AccountInfo<'info>],
+    profile_bump: u8,
+    allow_partial: bool,
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let cfg = &mut accounts.config;
+    require!(!cfg.paused, ErrorCode::ProtocolPaused);
+    require!(!cfg.staking_paused, ErrorCode::StakingPaused);
+    update_staking_global(cfg, now)?;
+
+    // Pull direct vault deposits into the books first so the debit below cannot underflow.
+    let native = xnt_is_native(cfg);
+    let available = if native {
+        vault_available_lamports(&accounts.staking_reward_vault)?
+    } else {
+        staking_xnt_vault_amount(cfg, remaining.first())?
+    };
+    sync_staking_vault_balance(cfg, available)?;
+
+    let profile = ensure_user_profile_v2(
+        &accounts.user_profile,
+        &accounts.owner.to_account_info(),
+        &accounts.system_program,
+        accounts.owner.key(),
+        profile_bump,
+        now,
+    )?;
+    require_keys_eq!(profile.owner, accounts.owner.key(), ErrorCode::Unauthorized);
+
+    let pending_base = pending_stake(cfg, &accounts.user_stake)?;
+    let base_total = pending_base
+        .checked_add(accounts.user_stake.reward_owed as u128)
+        .ok_or(ErrorCode::StakingMathOverflow)?;
+    require!(base_total > 0, ErrorCode::NothingToClaim);
+
+    let bonus_bps = profile.badge_bonus_bps.min(BADGE_BONUS_CAP_BPS) as u128;
+    let payout = base_total
+        .checked_mul(BPS_DENOMINATOR + bonus_bps)
+        .ok_or(ErrorCode::StakingMathOverflow)?
+        .checked_div(BPS_DENOMINATOR)
+        .ok_or(ErrorCode::StakingMathOverflow)?;
+    let payout_u64 = u64::try_from(payout).map_err(|_| ErrorCode::StakingMathOverflow)?;
+
+    let paid = if allow_partial {
+        require!(available > 0, ErrorCode::InsufficientVaultBalance);
+        payout_u64.min(available)
+    } else {
+        require!(available >= payout_u64, ErrorCode::InsufficientVaultBalance);
+        payout_u64
+    };
+
+    if native {
+        transfer_lamports(
+            &accounts.staking_reward_vault.to_account_info(),
+            &accounts.owner.to_account_info(),
+            paid,
+        )?;
+    } else {
+        require!(remaining.len() >= 4, ErrorCode::MissingSplXntAccounts);
+        let (vault, owner_ata, vault_authority, token_program) =
+            (&remaining[0], &remaining[1], &remaining[2], &remaining[3]);
+        require_keys_eq!(
+            token_program.key(),
+            token::ID,
+            ErrorCode::MissingSplXntAccounts
+        );
+        let signer_seeds: &[&[u8]] = &[VAULT_SEED, &[cfg.bumps.vault_authority]];
+        let expected_authority = Pubkey::create_program_address(signer_seeds, &crate::ID)
+            .map_err(|_| ErrorCode::MissingSplXntAccounts)?;
+        require_keys_eq!(
+            vault_authority.key(),
+            expected_authority,
+            ErrorCode::MissingSplXntAccounts
+        );
+        token::transfer(
+            CpiContext::new_with_signer(
+                token_program.clone(),
+                Transfer {
+                    from: vault.clone(),
+                    to: owner_ata.clone(),
+                    authority: vault_authority.clone(),
+                },
+                &[signer_seeds],
+            ),
+            paid,
+        )?;
+    }
+
+    // Carry the unpaid part forward in base (pre-bonus) units; the bonus is re-applied on claim.
+    let unpaid = payout_u64
+        .checked_sub(paid)
+        .ok_or(ErrorCode::StakingMathOverflow)?;
+    let remaining_owed = (unpaid as u128)
+        .checked_mul(BPS_DENOMINATOR)
+        .ok_or(ErrorCode::StakingMathOverflow)?
+        .checked_div(BPS_DENOMINATOR + bonus_bps)
+        .ok_or(ErrorCode::StakingMathOverflow)?;
+    accounts.user_stake.reward_owed =
+        u64::try_from(remaining_owed).map_err(|_| ErrorCode::StakingMathOverflow)?;
+    accounts.user_stake.reward_debt = earned_per_stake(
+        accounts.user_stake.staked_mind,
+        cfg.staking_acc_xnt_per_mind,
+    )?;
+
+    cfg.staking_accounted_balance = cfg
+        .staking_accounted_balance
+        .checked_sub(paid)
+        .ok_or(ErrorCode::StakingMathOverflow)?;
+
+    emit!(XntClaimed {
+        owner: accounts.owner.key(),
+        amount: paid,
+        bonus_bps: bonus_bps as u16,
+    });
+    if paid < payout_u64 {
+        emit!(XntClaimedPartial {
+            owner: accounts.owner.key(),
+            amount: paid,
+            remaining_owed: accounts.user_stake.reward_owed,
+        });
+    }
     Ok(())
 }
 
+/// Staking rewards are paid in native lamports unless `admin_use_spl_xnt` set an SPL mint.
+fn xnt_is_native(cfg: &Config) -> bool {
+    cfg.xnt_mint == System::id()
+}
+
+fn staking_xnt_vault_amount(cfg: &Config, info: Option<&AccountInfo>) -> Result<u64> {
+    let info = info.ok_or(ErrorCode::MissingSplXntAccounts)?;
+    require_keys_eq!(
+        info.key(),
+        cfg.staking_xnt_vault,
+        ErrorCode::MissingSplXntAccounts
+    );
+    let data = info.try_borrow_data()?;
+    let mut slice: &[u8] = &data;
+    let vault = TokenAccount::try_deserialize(&mut slice)?;
+    Ok(vault.amount)
+}
+
+/// Returns before any accumulator math when `now` has not advanced since the last update, e.g.
+/// on repeat calls from several staking instructions in one transaction.
 fn update_staking_global(cfg: &mut Account<Config>, now: i64) -> Result<()> {
     if now <= cfg.staking_last_update_ts {
         return Ok(());
     }
     if cfg.staking_reward_rate_xnt_per_sec == 0 || cfg.staking_total_staked_mind == 0 {
         cfg.staking_last_update_ts = now;
-        return Ok(());
+        return auto_roll_staking_epoch(cfg, now);
     }
     let effective_end = now.min(cfg.staking_epoch_end_ts);
     if effective_end <= cfg.staking_last_update_ts {
-        return Ok(());
+        return auto_roll_staking_epoch(cfg, now);
     }
+    let delta = staking_acc_delta(cfg, effective_end)?;
+    cfg.staking_acc_xnt_per_mind = cfg
+        .staking_acc_xnt_per_mind
+        .checked_add(delta)
+        .ok_or(ErrorCode::StakingMathOverflow)?;
+    cfg.staking_last_update_ts = effective_end;
+    auto_roll_staking_epoch(cfg, now)
+}
+
+fn staking_acc_delta(cfg: &Config, effective_end: i64) -> Result<u128> {
     let dt = effective_end
         .checked_sub(cfg.staking_last_update_ts)
-        .ok_or(ErrorCode::MathOverflow)?;
+        .ok_or(ErrorCode::StakingMathOverflow)?;
     let mintable = (dt as u128)
         .checked_mul(cfg.staking_reward_rate_xnt_per_sec as u128)
-        .ok_or(ErrorCode::MathOverflow)?;
-    let delta = mintable
+        .ok_or(ErrorCode::StakingMathOverflow)?;
+    mintable
         .checked_mul(ACC_SCALE)
-        .ok_or(ErrorCode::MathOverflow)?
+        .ok_or(ErrorCode::StakingMathOverflow)?
         .checked_div(cfg.staking_total_staked_mind as u128)
-        .ok_or(ErrorCode::MathOverflow)?;
-    cfg.staking_acc_xnt_per_mind = cfg
-        .staking_acc_xnt_per_mind
-        .checked_add(delta)
-        .ok_or(ErrorCode::MathOverflow)?;
-    cfg.staking_last_update_ts = effective_end;
+        .ok_or(ErrorCode::StakingMathOverflow.into())
+}
+
+/// Staking accumulator as `update_staking_global` would leave it at `now`, without mutating.
+fn simulated_staking_acc(cfg: &Config, now: i64) -> Result<u128> {
+    if now <= cfg.staking_last_update_ts
+        || cfg.staking_reward_rate_xnt_per_sec == 0
+        || cfg.staking_total_staked_mind == 0
+    {
+        return Ok(cfg.staking_acc_xnt_per_mind);
+    }
+    let effective_end = now.min(cfg.staking_epoch_end_ts);
+    if effective_end <= cfg.staking_last_update_ts {
+        return Ok(cfg.staking_acc_xnt_per_mind);
+    }
+    cfg.staking_acc_xnt_per_mind
+        .checked_add(staking_acc_delta(cfg, effective_end)?)
+        .ok_or(ErrorCode::StakingMathOverflow.into())
+}
+
+fn auto_roll_staking_epoch(cfg: &mut Account<Config>, now: i64) -> Result<()> {
+    if cfg.auto_roll_epoch_seconds == 0 || now < cfg.staking_epoch_end_ts {
+        return Ok(());
+    }
+    if cfg.staking_total_staked_mind == 0 || cfg.staking_undistributed_xnt == 0 {
+        return Ok(());
+    }
+    let epoch_seconds = cfg.auto_roll_epoch_seconds;
+    let rate = start_staking_epoch(cfg, now, epoch_seconds)?;
+    if rate > 0 {
+        emit!(EpochRolled {
+            rate,
+            epoch_end_ts: cfg.staking_epoch_end_ts,
+            epoch_seq: cfg.epoch_seq,
+        });
+    }
+    Ok(())
+}
+
+/// Books new vault deposits into `staking_undistributed_xnt`, or trims it when the vault
+/// holds less than what has been accounted for.
+fn sync_staking_vault_balance(cfg: &mut Config, vault_balance: u64) -> Result<()> {
+    if vault_balance > cfg.staking_accounted_balance {
+        let delta = vault_balance
+            .checked_sub(cfg.staking_accounted_balance)
+            .ok_or(ErrorCode::StakingMathOverflow)?;
+        cfg.staking_undistributed_xnt = cfg
+            .staking_undistributed_xnt
+            .checked_add(delta)
+            .ok_or(ErrorCode::StakingMathOverflow)?;
+        cfg.staking_accounted_balance = vault_balance;
+    } else if vault_balance < cfg.staking_accounted_balance {
+        // Never commit more than the vault can still pay out on top of what is already owed.
+        let shortfall = cfg
+            .staking_accounted_balance
+            .checked_sub(vault_balance)
+            .ok_or(ErrorCode::StakingMathOverflow)?;
+        let trimmed = shortfall.min(cfg.staking_undistributed_xnt);
+        cfg.staking_undistributed_xnt = cfg
+            .staking_undistributed_xnt
+            .checked_sub(trimmed)
+            .ok_or(ErrorCode::StakingMathOverflow)?;
+        cfg.staking_accounted_balance = cfg
+            .staking_accounted_balance
+            .checked_sub(trimmed)
+            .ok_or(ErrorCode::StakingMathOverflow)?;
+        emit!(StakingVaultShortfall {
+            vault_balance,
+            shortfall,
+            trimmed_undistributed_xnt: trimmed,
+        });
+    }
+    Ok(())
+}
+
+fn roll_staking_epoch(
+    cfg: &mut Config,
+    vault_balance: u64,
+    now: i64,
+    epoch_seconds: u64,
+) -> Result<()> {
+    let rate = start_staking_epoch(cfg, now, epoch_seconds)?;
+    let committed = rate
+        .checked_mul(epoch_seconds)
+        .ok_or(ErrorCode::StakingMathOverflow)?;
+    require!(
+        committed <= vault_balance,
+        ErrorCode::InsufficientVaultBalance
+    );
+    if rate == 0 {
+        return Ok(());
+    }
+
+    emit!(EpochRolled {
+        rate,
+        epoch_end_ts: cfg.staking_epoch_end_ts,
+        epoch_seq: cfg.epoch_seq,
+    });
     Ok(())
 }
 
+fn start_staking_epoch(cfg: &mut Config, now: i64, epoch_seconds: u64) -> Result<u64> {
+    require!(epoch_seconds > 0, ErrorCode::InvalidEpochSeconds);
+    if cfg.staking_total_staked_mind == 0 || cfg.staking_undistributed_xnt == 0 {
+        cfg.staking_reward_rate_xnt_per_sec = 0;
+        cfg.staking_epoch_end_ts = now;
+        cfg.staking_last_update_ts = now;
+        return Ok(0);
+    }
+
+    let rate = cfg
+        .staking_undistributed_xnt
+        .checked_div(epoch_seconds)
+        .ok_or(ErrorCode::StakingMathOverflow)?;
+    if rate == 0 {
+        cfg.staking_reward_rate_xnt_per_sec = 0;
+        cfg.staking_epoch_end_ts = now;
+        cfg.staking_last_update_ts = now;
+        return Ok(0);
+    }
+    let distributed = rate
+        .checked_mul(epoch_seconds)
+        .ok_or(ErrorCode::StakingMathOverflow)?;
+    cfg.staking_undistributed_xnt = cfg
+        .staking_undistributed_xnt
+        .checked_sub(distributed)
+        .ok_or(ErrorCode::StakingMathOverflow)?;
+    cfg.staking_reward_rate_xnt_per_sec = rate;
+    cfg.epoch_seq = cfg
+        .epoch_seq
+        .checked_add(1)
+        .ok_or(ErrorCode::StakingMathOverflow)?;
+    cfg.staking_epoch_end_ts = now
+        .checked_add(epoch_seconds as i64)
+        .ok_or(ErrorCode::StakingMathOverflow)?;
+    cfg.staking_last_update_ts = now;
+    Ok(rate)
+}
+
 #[error_code]
 pub enum ErrorCode {
     #[msg("Invalid amount")]
@@ -3757,4 +7354,226 @@ pub enum ErrorCode {
     ProfileSyncRequired,
     #[msg("Invalid sync positions")]
     InvalidSyncPositions,
+    #[msg("Invalid user stake account")]
+    InvalidUserStake,
+    #[msg("Rig buff upgrade still in cooldown")]
+    BuffUpgradeCooldown,
+    #[msg("Invalid rig buff config")]
+    InvalidRigBuffConfig,
+    #[msg("HP scaling not enabled")]
+    HpScaleNotEnabled,
+    #[msg("Position is not a pre-scaling expired position")]
+    PositionNotRescalable,
+    #[msg("Invalid position repair parameters")]
+    InvalidRepairParams,
+    #[msg("Unstake cooldown enabled; use request_unstake")]
+    UnstakeCooldownEnabled,
+    #[msg("Unstake cooldown disabled; use unstake_mind")]
+    UnstakeCooldownDisabled,
+    #[msg("Unstake cooldown still active")]
+    UnstakeCooldownActive,
+    #[msg("Treasury sweep not configured")]
+    TreasurySweepDisabled,
+    #[msg("Treasury balance not above sweep threshold")]
+    TreasuryBelowThreshold,
+    #[msg("Missing or invalid SPL XNT accounts")]
+    MissingSplXntAccounts,
+    #[msg("Invalid config account")]
+    InvalidConfigAccount,
+    #[msg("Math overflow in mining emission accumulator")]
+    EmissionMathOverflow,
+    #[msg("Math overflow in HP accounting")]
+    HpMathOverflow,
+    #[msg("Math overflow in staking reward accounting")]
+    StakingMathOverflow,
+    #[msg("Consecutive renew cap reached; let the rig deactivate and buy a new one")]
+    RenewCapReached,
+    #[msg("Staker emission share exceeds 100%")]
+    InvalidStakerEmissionBps,
+    #[msg("Too many epoch rollers")]
+    TooManyEpochRollers,
+    #[msg("Staking is paused")]
+    StakingPaused,
+    #[msg("Too many positions in one batch")]
+    TooManyPositions,
+    #[msg("Invalid admin candidate")]
+    InvalidAdminCandidate,
+    #[msg("Protocol is paused")]
+    ProtocolPaused,
+    #[msg("Staking epoch has not ended yet")]
+    EpochNotEnded,
+    #[msg("No undistributed XNT to roll into an epoch")]
+    NothingToDistribute,
+    #[msg("Level bonus exceeds cap")]
+    InvalidLevelBonus,
+    #[msg("Stake is still within its lock period")]
+    StakeLockActive,
+    #[msg("Position is not deactivated")]
+    PositionNotDeactivated,
+    #[msg("Position still has unclaimed rewards")]
+    UnclaimedRewards,
+    #[msg("Invalid delegate permissions")]
+    InvalidDelegatePermissions,
+    #[msg("Invalid delegate")]
+    InvalidDelegate,
+    #[msg("Delegate lacks permission for this instruction")]
+    DelegatePermissionDenied,
+    #[msg("Badge merkle root not set")]
+    BadgeMerkleRootNotSet,
+    #[msg("Invalid merkle proof")]
+    InvalidMerkleProof,
+    #[msg("Crank accounts must be position/profile pairs")]
+    InvalidCrankAccounts,
+    #[msg("Position HP below configured minimum")]
+    PositionHpTooLow,
+    #[msg("Staking share must be at most 10000 bps")]
+    InvalidStakingShareBps,
+    #[msg("Invalid level bonus table account")]
+    InvalidLevelBonusTable,
+    #[msg("Level bonus table account is required once initialized")]
+    LevelBonusTableRequired,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Settles 1000 claims against a growing accumulator and returns the final drift of the
+    /// paid total from the unrounded `hp * acc / ACC_SCALE`, in ACC_SCALE units.
+    fn settle_drift(round_half_up: bool) -> i128 {
+        let hp: u128 = 1_234_567;
+        let mut acc: u128 = 0;
+        let mut debt: u128 = 0;
+        let mut paid: u128 = 0;
+        let mut seed: u128 = 0x2545_f491;
+        for _ in 0..1000 {
+            seed = seed.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1) % (1 << 64);
+            let prev_exact = hp * acc;
+            acc += ACC_SCALE / 1_000 + seed % ACC_SCALE;
+            let pending = pending_mind(hp, acc, debt, round_half_up).unwrap();
+            let settle_drift = (hp * acc - prev_exact) as i128 - (pending * ACC_SCALE) as i128;
+            assert!(settle_drift.unsigned_abs() < ACC_SCALE);
+            paid += pending;
+            debt = earned_per_hp_rounded(hp, acc, round_half_up).unwrap();
+        }
+        assert_eq!(paid, debt);
+        (hp * acc) as i128 - (paid * ACC_SCALE) as i128
+    }
+
+    #[test]
+    fn half_up_rounding_stays_within_half_a_unit_over_1000_claims() {
+        let drift = settle_drift(true);
+        assert!(drift.unsigned_abs() <= ACC_SCALE / 2);
+    }
+
+    #[test]
+    fn migrated_stake_top_up_stays_locked() {
+        let lock_seconds = 30 * 86_400;
+        let now = 1_700_000_000;
+        let legacy = UserStake {
+            owner: Pubkey::default(),
+            staked_mind: 1_000,
+            reward_debt: 0,
+            reward_owed: 0,
+            bump: 0,
+            mind_reward_debt: 0,
+            mind_reward_owed: 0,
+            stake_start_ts: 0,
+            pending_unstake: 0,
+            unstake_available_ts: 0,
+        };
+        let start = weighted_stake_start_ts(&legacy, 1_000, now, lock_seconds).unwrap();
+        assert_eq!(start, now - lock_seconds / 2);
+        assert!(start + lock_seconds > now);
+    }
+
+    fn legacy_position(hp: u64, final_acc: u128) -> PositionData {
+        PositionData {
+            owner: Pubkey::default(),
+            hp,
+            start_ts: 0,
+            end_ts: 100,
+            reward_debt: 0,
+            final_acc_mind_per_hp: final_acc,
+            deactivated: false,
+            bump: 0,
+            rig_type: 0,
+            buff_level: 0,
+            hp_scaled: false,
+            expired: true,
+            buff_applied_from_cycle: 0,
+            last_level_applied: 1,
+            consecutive_renews: 0,
+            last_buff_upgrade_ts: 0,
+            level_bonus_bps: LEVEL_BONUS_UNRECORDED,
+            version: 3,
+        }
+    }
+
+    #[test]
+    fn legacy_expired_position_pays_unscaled_amount() {
+        let hp = 15;
+        let final_acc = 7 * ACC_SCALE * HP_SCALE;
+        let mut position = legacy_position(hp, final_acc);
+        assert!(rescale_legacy_expired(&mut position).unwrap());
+        assert!(position.hp_scaled);
+        let hp_scaled = position_base_hp_scaled(&position).unwrap();
+        let paid = pending_mind(hp_scaled, position.final_acc_mind_per_hp, 0, false).unwrap();
+        assert_eq!(paid, hp as u128 * final_acc / ACC_SCALE);
+
+        // Already converted, scaled or still active positions are left alone.
+        assert!(!rescale_legacy_expired(&mut position).unwrap());
+        let mut active = legacy_position(hp, 0);
+        active.expired = false;
+        assert!(!rescale_legacy_expired(&mut active).unwrap());
+    }
+
+    #[test]
+    fn level_bonus_change_only_applies_to_later_rewards() {
+        let acc = 40 * ACC_SCALE;
+        let mut position = legacy_position(1_000 * HP_SCALE_U64, 0);
+        position.hp_scaled = true;
+        position.expired = false;
+        let base_hp = position_base_hp_scaled(&position).unwrap();
+        position.reward_debt = earned_per_hp(base_hp, acc).unwrap();
+        let profile = UserMiningProfile {
+            owner: Pubkey::default(),
+            next_position_index: 1,
+            active_hp: 1_000 * HP_SCALE_U64,
+            buffed_hp: 1_000 * HP_SCALE_U64,
+            buffed_hp_synced: true,
+            xp: 0,
+            badge_tier: 0,
+            badge_bonus_bps: 0,
+            bump: 0,
+            level: 1,
+            last_xp_update_ts: 0,
+            hp_scaled: true,
+            level_acc_snapshots: [0; 7],
+            level_bonus_bps: 500,
+        };
+        apply_level_updates_to_position(&mut position, &profile, 0, 0, acc, None).unwrap();
+        assert_eq!(position.level_bonus_bps, 500);
+
+        let hp_effective = effective_hp_with_bonus(base_hp, 0, 500).unwrap();
+        assert_eq!(
+            pending_mind(hp_effective, acc, position.reward_debt, false).unwrap(),
+            0
+        );
+        let later = pending_mind(hp_effective, 2 * acc, position.reward_debt, false).unwrap();
+        assert_eq!(later, earned_per_hp(hp_effective, acc).unwrap());
+    }
+
+    #[test]
+    fn staking_overflow_reports_staking_error() {
+        let err = earned_per_stake(u64::MAX, u128::MAX).unwrap_err();
+        assert_eq!(err, ErrorCode::StakingMathOverflow.into());
+    }
+
+    #[test]
+    fn round_down_never_overpays_over_1000_claims() {
+        let drift = settle_drift(false);
+        assert!(drift >= 0);
+        assert!((drift as u128) < ACC_SCALE);
+    }
 }
@@ -14,6 +14,7 @@ import {
   deriveConfigPda,
   deriveProfilePda,
   deriveVaultPda,
+  levelBonusTableAccountMeta,
 } from "./v2-common";
 import {
   decodeMinerPositionAccount,
@@ -112,6 +113,7 @@ async function main() {
         userMindAta: ata,
         tokenProgram: TOKEN_PROGRAM_ID,
       })
+      .remainingAccounts([levelBonusTableAccountMeta()])
       .instruction();
     tx.add(instruction);
   }
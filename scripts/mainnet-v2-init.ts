@@ -97,6 +97,7 @@ const main = async () => {
         emissionPerSec: new anchor.BN(emissionPerSec.toString()),
         maxEffectiveHp: new anchor.BN(maxEffectiveHp.toString()),
         secondsPerDay: new anchor.BN(secondsPerDay.toString()),
+        stakingShareBps: 0,
       })
       .accounts({
         payer: wallet.publicKey,
@@ -18,6 +18,7 @@ import {
   fetchUserProfile,
   getProgram,
   getProvider,
+  levelBonusTableAccountMeta,
 } from "./v2-common";
 
 dotenv.config();
@@ -101,6 +102,7 @@ const main = async () => {
         tokenProgram: TOKEN_PROGRAM_ID,
         systemProgram: SystemProgram.programId,
       })
+      .remainingAccounts([levelBonusTableAccountMeta()])
       .rpc();
   } catch (err) {
     console.warn("Buy starter failed, skipping smoke.", err);
@@ -122,6 +124,7 @@ const main = async () => {
         userMindAta: ownerMindAta,
         tokenProgram: TOKEN_PROGRAM_ID,
       })
+      .remainingAccounts([levelBonusTableAccountMeta()])
       .rpc();
   } catch (err) {
     console.warn("Claim failed or nothing to claim yet.", err);
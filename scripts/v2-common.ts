@@ -141,6 +141,17 @@ export const deriveLevelConfigPda = () =>
 export const deriveHpScaleConfigPda = () =>
   PublicKey.findProgramAddressSync([Buffer.from("hp_scale")], PROGRAM_ID)[0];
 
+export const deriveLevelBonusTablePda = () =>
+  PublicKey.findProgramAddressSync([Buffer.from("level_bonus_table")], PROGRAM_ID)[0];
+
+// The pinned IDL predates the optional level bonus table account, which the program
+// requires once the table exists; it goes right after the IDL's accounts.
+export const levelBonusTableAccountMeta = () => ({
+  pubkey: deriveLevelBonusTablePda(),
+  isWritable: false,
+  isSigner: false,
+});
+
 export const deriveVaultPda = () =>
   PublicKey.findProgramAddressSync([Buffer.from("vault")], PROGRAM_ID)[0];
 
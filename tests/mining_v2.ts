@@ -8,6 +8,7 @@ import {
   createMint,
   getAccount,
   getAssociatedTokenAddressSync,
+  mintTo,
   transfer,
 } from "@solana/spl-token";
 import {
@@ -121,7 +122,7 @@ describe("mining_v2", () => {
   const userB = Keypair.generate();
 
   const MIND_DECIMALS = 9;
-  const EMISSION_PER_SEC = new BN(1_000_000_000); // 1 MIND/sec
+  const EMISSION_PER_SEC = new BN(11_574_074); // on-chain EMISSION_PER_SEC_MAX
   const MAX_EFFECTIVE_HP = new BN(50);
   const SECONDS_PER_DAY = new BN(1); // speed up tests
   const ACC_SCALE = new BN("1000000000000000000");
@@ -135,6 +136,10 @@ describe("mining_v2", () => {
     [Buffer.from("config")],
     program.programId
   );
+  const [levelBonusTablePda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("level_bonus_table")],
+    program.programId
+  );
   const [levelConfigPda] = PublicKey.findProgramAddressSync(
     [Buffer.from("level_config")],
     program.programId
@@ -218,6 +223,80 @@ describe("mining_v2", () => {
   const calcAccDelta = (emission: BN, dt: BN, totalHp: BN) =>
     emission.mul(dt).mul(ACC_SCALE).div(totalHp);
 
+  const newUser = async (sol = 50) => {
+    const user = Keypair.generate();
+    await airdrop(user.publicKey, sol);
+    await createAssociatedTokenAccountIdempotent(
+      provider.connection,
+      admin,
+      mindMint,
+      user.publicKey
+    );
+    return user;
+  };
+
+  const buyContractFor = async (user: Keypair, contractType: number, index: number) =>
+    program.methods
+      .buyContract(contractType, new BN(index))
+      .accounts({
+        owner: user.publicKey,
+        config: configPda,
+        userProfile: profilePda(user.publicKey),
+        position: positionPda(user.publicKey, index),
+        stakingRewardVault,
+        treasuryVault,
+        systemProgram: SystemProgram.programId,
+        levelBonusTable: levelBonusTablePda,
+      })
+      .signers([user])
+      .rpc();
+
+  const claimMindFor = async (user: Keypair, index: number) =>
+    program.methods
+      .claimMind()
+      .accounts({
+        owner: user.publicKey,
+        config: configPda,
+        userProfile: profilePda(user.publicKey),
+        position: positionPda(user.publicKey, index),
+        vaultAuthority,
+        mindMint,
+        userMindAta: userMindAta(user.publicKey),
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        levelBonusTable: levelBonusTablePda,
+      })
+      .signers([user])
+      .rpc();
+
+  const stakeMindFor = async (user: Keypair, amount: BN) =>
+    program.methods
+      .stakeMind(amount)
+      .accounts({
+        owner: user.publicKey,
+        config: configPda,
+        userProfile: profilePda(user.publicKey),
+        userStake: stakePda(user.publicKey),
+        vaultAuthority,
+        stakingMindVault,
+        ownerMindAta: userMindAta(user.publicKey),
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([user])
+      .rpc();
+
+  const fundStakingVault = async (lamports: number) => {
+    const fundTx = new Transaction().add(
+      SystemProgram.transfer({
+        fromPubkey: admin.publicKey,
+        toPubkey: stakingRewardVault,
+        lamports,
+      })
+    );
+    await provider.sendAndConfirm(fundTx, [admin]);
+  };
+
   let stressPositions: Array<{ owner: Keypair; index: number }> = [];
 
   before(async () => {
@@ -264,11 +343,49 @@ describe("mining_v2", () => {
       );
     }
 
+    // init_config can only run once, so the mismatched-decimals rejection is checked here.
+    const sixDecimalMint = await createMint(provider.connection, admin, vaultAuthority, null, 6);
+    const sixDecimalVault = await createAccount(
+      provider.connection,
+      admin,
+      sixDecimalMint,
+      vaultAuthority,
+      Keypair.generate()
+    );
+    try {
+      await program.methods
+        .initConfig({
+          emissionPerSec: EMISSION_PER_SEC,
+          maxEffectiveHp: MAX_EFFECTIVE_HP,
+          secondsPerDay: SECONDS_PER_DAY,
+          stakingShareBps: 0,
+        })
+        .accounts({
+          payer: admin.publicKey,
+          admin: admin.publicKey,
+          programData,
+          vaultAuthority,
+          config: configPda,
+          mindMint: sixDecimalMint,
+          stakingRewardVault,
+          treasuryVault,
+          stakingMindVault: sixDecimalVault,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([admin])
+        .rpc();
+      expect.fail("Expected init_config to reject a non-9-decimal MIND mint");
+    } catch (err) {
+      expect(`${err}`).to.include("InvalidMintDecimals");
+    }
+
     await program.methods
       .initConfig({
         emissionPerSec: EMISSION_PER_SEC,
         maxEffectiveHp: MAX_EFFECTIVE_HP,
         secondsPerDay: SECONDS_PER_DAY,
+        stakingShareBps: 0,
       })
       .accounts({
         payer: admin.publicKey,
@@ -329,6 +446,7 @@ describe("mining_v2", () => {
         stakingRewardVault,
         treasuryVault,
         systemProgram: SystemProgram.programId,
+        levelBonusTable: levelBonusTablePda,
       })
       .signers([userA])
       .rpc();
@@ -352,6 +470,7 @@ describe("mining_v2", () => {
         stakingRewardVault,
         treasuryVault,
         systemProgram: SystemProgram.programId,
+        levelBonusTable: levelBonusTablePda,
       })
       .signers([userB])
       .rpc();
@@ -375,6 +494,7 @@ describe("mining_v2", () => {
         userMindAta: userMindAta(userA.publicKey),
         tokenProgram: TOKEN_PROGRAM_ID,
         systemProgram: SystemProgram.programId,
+        levelBonusTable: levelBonusTablePda,
       })
       .signers([userA])
       .rpc();
@@ -394,6 +514,7 @@ describe("mining_v2", () => {
         userMindAta: userMindAta(userB.publicKey),
         tokenProgram: TOKEN_PROGRAM_ID,
         systemProgram: SystemProgram.programId,
+        levelBonusTable: levelBonusTablePda,
       })
       .signers([userB])
       .rpc();
@@ -445,6 +566,7 @@ describe("mining_v2", () => {
         position: positionPda(userA.publicKey, 0),
         userProfile: profilePda(userA.publicKey),
         systemProgram: SystemProgram.programId,
+        levelBonusTable: levelBonusTablePda,
       })
       .signers([userA])
       .rpc();
@@ -566,6 +688,7 @@ describe("mining_v2", () => {
           stakingRewardVault,
           treasuryVault,
           systemProgram: SystemProgram.programId,
+          levelBonusTable: levelBonusTablePda,
         })
         .signers([user])
         .rpc();
@@ -596,6 +719,7 @@ describe("mining_v2", () => {
           userMindAta: userMindAta(owner.publicKey),
           tokenProgram: TOKEN_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
+          levelBonusTable: levelBonusTablePda,
         })
         .signers([owner])
         .rpc();
@@ -655,6 +779,7 @@ describe("mining_v2", () => {
             position: positionPda(item.owner.publicKey, item.index),
             userProfile: profilePda(item.owner.publicKey),
             systemProgram: SystemProgram.programId,
+            levelBonusTable: levelBonusTablePda,
           })
           .signers([item.owner])
           .rpc();
@@ -676,6 +801,7 @@ describe("mining_v2", () => {
         position: positionPda(userA.publicKey, 0),
         userProfile: profilePda(userA.publicKey),
         systemProgram: SystemProgram.programId,
+        levelBonusTable: levelBonusTablePda,
       })
       .signers([userA])
       .rpc();
@@ -706,6 +832,7 @@ describe("mining_v2", () => {
           stakingRewardVault,
           treasuryVault,
           systemProgram: SystemProgram.programId,
+          levelBonusTable: levelBonusTablePda,
         })
         .signers([user])
         .rpc();
@@ -724,6 +851,7 @@ describe("mining_v2", () => {
           userMindAta: userMindAta(user.publicKey),
           tokenProgram: TOKEN_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
+          levelBonusTable: levelBonusTablePda,
         })
         .signers([user])
         .rpc();
@@ -882,6 +1010,7 @@ describe("mining_v2", () => {
         stakingRewardVault,
         treasuryVault,
         systemProgram: SystemProgram.programId,
+        levelBonusTable: levelBonusTablePda,
       })
       .signers([user])
       .rpc();
@@ -905,6 +1034,7 @@ describe("mining_v2", () => {
         userMindAta: userMindAta(user.publicKey),
         tokenProgram: TOKEN_PROGRAM_ID,
         systemProgram: SystemProgram.programId,
+        levelBonusTable: levelBonusTablePda,
       })
       .signers([user])
       .rpc();
@@ -937,6 +1067,7 @@ describe("mining_v2", () => {
         stakingRewardVault,
         treasuryVault,
         systemProgram: SystemProgram.programId,
+        levelBonusTable: levelBonusTablePda,
       })
       .signers([user])
       .rpc();
@@ -954,6 +1085,7 @@ describe("mining_v2", () => {
           treasuryMindVault: mindTreasuryVault,
           tokenProgram: TOKEN_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
+          levelBonusTable: levelBonusTablePda,
         })
         .signers([user])
         .rpc();
@@ -983,6 +1115,7 @@ describe("mining_v2", () => {
         stakingRewardVault,
         treasuryVault,
         systemProgram: SystemProgram.programId,
+        levelBonusTable: levelBonusTablePda,
       })
       .signers([user])
       .rpc();
@@ -997,6 +1130,7 @@ describe("mining_v2", () => {
         position: positionPda(user.publicKey, 0),
         userProfile: profilePda(user.publicKey),
         systemProgram: SystemProgram.programId,
+        levelBonusTable: levelBonusTablePda,
       })
       .signers([user])
       .rpc();
@@ -1011,6 +1145,7 @@ describe("mining_v2", () => {
         stakingRewardVault,
         treasuryVault,
         systemProgram: SystemProgram.programId,
+        levelBonusTable: levelBonusTablePda,
       })
       .signers([user])
       .rpc();
@@ -1028,6 +1163,7 @@ describe("mining_v2", () => {
         treasuryMindVault: mindTreasuryVault,
         tokenProgram: TOKEN_PROGRAM_ID,
         systemProgram: SystemProgram.programId,
+        levelBonusTable: levelBonusTablePda,
       })
         .signers([user])
         .rpc();
@@ -1057,6 +1193,7 @@ describe("mining_v2", () => {
         stakingRewardVault,
         treasuryVault,
         systemProgram: SystemProgram.programId,
+        levelBonusTable: levelBonusTablePda,
       })
       .signers([levelUser])
       .rpc();
@@ -1074,6 +1211,7 @@ describe("mining_v2", () => {
         userMindAta: userMindAta(levelUser.publicKey),
         tokenProgram: TOKEN_PROGRAM_ID,
         systemProgram: SystemProgram.programId,
+        levelBonusTable: levelBonusTablePda,
       })
       .signers([levelUser])
       .rpc();
@@ -1088,6 +1226,7 @@ describe("mining_v2", () => {
         stakingRewardVault,
         treasuryVault,
         systemProgram: SystemProgram.programId,
+        levelBonusTable: levelBonusTablePda,
       })
       .signers([levelUser])
       .rpc();
@@ -1104,6 +1243,7 @@ describe("mining_v2", () => {
         treasuryMindVault: mindTreasuryVault,
         tokenProgram: TOKEN_PROGRAM_ID,
         systemProgram: SystemProgram.programId,
+        levelBonusTable: levelBonusTablePda,
       })
       .signers([levelUser])
       .rpc();
@@ -1128,6 +1268,7 @@ describe("mining_v2", () => {
         userMindAta: userMindAta(levelUser.publicKey),
         tokenProgram: TOKEN_PROGRAM_ID,
         systemProgram: SystemProgram.programId,
+        levelBonusTable: levelBonusTablePda,
       })
       .signers([levelUser])
       .rpc();
@@ -1148,6 +1289,7 @@ describe("mining_v2", () => {
         treasuryMindVault: mindTreasuryVault,
         tokenProgram: TOKEN_PROGRAM_ID,
         systemProgram: SystemProgram.programId,
+        levelBonusTable: levelBonusTablePda,
       })
       .signers([levelUser])
       .rpc();
@@ -1189,6 +1331,7 @@ describe("mining_v2", () => {
         stakingRewardVault,
         treasuryVault,
         systemProgram: SystemProgram.programId,
+        levelBonusTable: levelBonusTablePda,
       })
       .signers([user])
       .rpc();
@@ -1206,6 +1349,7 @@ describe("mining_v2", () => {
         userMindAta: userMindAta(user.publicKey),
         tokenProgram: TOKEN_PROGRAM_ID,
         systemProgram: SystemProgram.programId,
+        levelBonusTable: levelBonusTablePda,
       })
       .signers([user])
       .rpc();
@@ -1220,6 +1364,7 @@ describe("mining_v2", () => {
         stakingRewardVault,
         treasuryVault,
         systemProgram: SystemProgram.programId,
+        levelBonusTable: levelBonusTablePda,
       })
       .signers([user])
       .rpc();
@@ -1242,6 +1387,7 @@ describe("mining_v2", () => {
         userMindAta: userMindAta(user.publicKey),
         tokenProgram: TOKEN_PROGRAM_ID,
         systemProgram: SystemProgram.programId,
+        levelBonusTable: levelBonusTablePda,
       })
       .signers([user])
       .rpc();
@@ -1265,6 +1411,7 @@ describe("mining_v2", () => {
         treasuryMindVault: mindTreasuryVault,
         tokenProgram: TOKEN_PROGRAM_ID,
         systemProgram: SystemProgram.programId,
+        levelBonusTable: levelBonusTablePda,
       })
       .signers([user])
       .rpc();
@@ -1302,6 +1449,7 @@ describe("mining_v2", () => {
         stakingRewardVault,
         treasuryVault,
         systemProgram: SystemProgram.programId,
+        levelBonusTable: levelBonusTablePda,
       })
       .signers([user])
       .rpc();
@@ -1323,6 +1471,7 @@ describe("mining_v2", () => {
           stakingRewardVault,
           treasuryVault,
           systemProgram: SystemProgram.programId,
+          levelBonusTable: levelBonusTablePda,
         })
         .signers([user])
         .rpc();
@@ -1343,6 +1492,7 @@ describe("mining_v2", () => {
           stakingRewardVault,
           treasuryVault,
           systemProgram: SystemProgram.programId,
+          levelBonusTable: levelBonusTablePda,
         })
         .signers([user])
         .rpc();
@@ -1362,6 +1512,7 @@ describe("mining_v2", () => {
         userMindAta: userMindAta(user.publicKey),
         tokenProgram: TOKEN_PROGRAM_ID,
         systemProgram: SystemProgram.programId,
+        levelBonusTable: levelBonusTablePda,
       })
       .signers([user])
       .rpc();
@@ -1382,6 +1533,7 @@ describe("mining_v2", () => {
         treasuryMindVault: mindTreasuryVault,
         tokenProgram: TOKEN_PROGRAM_ID,
         systemProgram: SystemProgram.programId,
+        levelBonusTable: levelBonusTablePda,
       })
       .signers([user])
       .rpc();
@@ -1413,6 +1565,7 @@ describe("mining_v2", () => {
         stakingRewardVault,
         treasuryVault,
         systemProgram: SystemProgram.programId,
+        levelBonusTable: levelBonusTablePda,
       })
       .signers([proUser])
       .rpc();
@@ -1432,6 +1585,7 @@ describe("mining_v2", () => {
           userMindAta: userMindAta(proUser.publicKey),
           tokenProgram: TOKEN_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
+          levelBonusTable: levelBonusTablePda,
         })
         .signers([proUser])
         .rpc();
@@ -1451,6 +1605,7 @@ describe("mining_v2", () => {
           treasuryMindVault: mindTreasuryVault,
           tokenProgram: TOKEN_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
+          levelBonusTable: levelBonusTablePda,
         })
         .signers([proUser])
         .rpc();
@@ -1480,6 +1635,7 @@ describe("mining_v2", () => {
         stakingRewardVault,
         treasuryVault,
         systemProgram: SystemProgram.programId,
+        levelBonusTable: levelBonusTablePda,
       })
       .signers([indUser])
       .rpc();
@@ -1499,6 +1655,7 @@ describe("mining_v2", () => {
           userMindAta: userMindAta(indUser.publicKey),
           tokenProgram: TOKEN_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
+          levelBonusTable: levelBonusTablePda,
         })
         .signers([indUser])
         .rpc();
@@ -1518,6 +1675,7 @@ describe("mining_v2", () => {
           treasuryMindVault: mindTreasuryVault,
           tokenProgram: TOKEN_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
+          levelBonusTable: levelBonusTablePda,
         })
         .signers([indUser])
         .rpc();
@@ -1549,6 +1707,7 @@ describe("mining_v2", () => {
         stakingRewardVault,
         treasuryVault,
         systemProgram: SystemProgram.programId,
+        levelBonusTable: levelBonusTablePda,
       })
       .signers([user])
       .rpc();
@@ -1566,6 +1725,7 @@ describe("mining_v2", () => {
           stakingRewardVault,
           treasuryVault,
           systemProgram: SystemProgram.programId,
+          levelBonusTable: levelBonusTablePda,
         })
         .signers([user])
         .rpc();
@@ -1582,6 +1742,7 @@ describe("mining_v2", () => {
         position: positionPda(user.publicKey, 0),
         userProfile: profilePda(user.publicKey),
         systemProgram: SystemProgram.programId,
+        levelBonusTable: levelBonusTablePda,
       })
       .signers([user])
       .rpc();
@@ -1659,6 +1820,7 @@ describe("mining_v2", () => {
         stakingRewardVault,
         treasuryVault,
         systemProgram: SystemProgram.programId,
+        levelBonusTable: levelBonusTablePda,
       })
       .signers([staker])
       .rpc();
@@ -1677,6 +1839,7 @@ describe("mining_v2", () => {
         userMindAta: userMindAta(staker.publicKey),
         tokenProgram: TOKEN_PROGRAM_ID,
         systemProgram: SystemProgram.programId,
+        levelBonusTable: levelBonusTablePda,
       })
       .signers([staker])
       .rpc();
@@ -1721,5 +1884,2036 @@ describe("mining_v2", () => {
     expect(cfgAfterActive.staking_total_staked_mind).to.be.greaterThan(0);
     expect(cfgAfterActive.staking_reward_rate_xnt_per_sec).to.eq(0);
   });
+
+  it("auto-rolls the staking epoch across an epoch boundary", async () => {
+    const staker = Keypair.generate();
+    await airdrop(staker.publicKey, 20);
+    await createAssociatedTokenAccountIdempotent(
+      provider.connection,
+      admin,
+      mindMint,
+      staker.publicKey
+    );
+
+    await program.methods
+      .buyContract(0, new BN(0))
+      .accounts({
+        owner: staker.publicKey,
+        config: configPda,
+        userProfile: profilePda(staker.publicKey),
+        position: positionPda(staker.publicKey, 0),
+        stakingRewardVault,
+        treasuryVault,
+        systemProgram: SystemProgram.programId,
+        levelBonusTable: levelBonusTablePda,
+      })
+      .signers([staker])
+      .rpc();
+    await sleep(1200);
+    await program.methods
+      .claimMind()
+      .accounts({
+        owner: staker.publicKey,
+        config: configPda,
+        userProfile: profilePda(staker.publicKey),
+        position: positionPda(staker.publicKey, 0),
+        vaultAuthority,
+        mindMint,
+        userMindAta: userMindAta(staker.publicKey),
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        levelBonusTable: levelBonusTablePda,
+      })
+      .signers([staker])
+      .rpc();
+    const minted = await getTokenAmount(userMindAta(staker.publicKey));
+    await program.methods
+      .stakeMind(minted.divn(2))
+      .accounts({
+        owner: staker.publicKey,
+        config: configPda,
+        userProfile: profilePda(staker.publicKey),
+        userStake: stakePda(staker.publicKey),
+        vaultAuthority,
+        stakingMindVault,
+        ownerMindAta: userMindAta(staker.publicKey),
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([staker])
+      .rpc();
+
+    await program.methods
+      .adminSetAutoRollEpoch(new BN(5))
+      .accounts({ admin: admin.publicKey, config: configPda })
+      .signers([admin])
+      .rpc();
+
+    // A purchase funds `staking_undistributed_xnt` without anyone calling `roll_epoch`.
+    await program.methods
+      .buyContract(1, new BN(1))
+      .accounts({
+        owner: staker.publicKey,
+        config: configPda,
+        userProfile: profilePda(staker.publicKey),
+        position: positionPda(staker.publicKey, 1),
+        stakingRewardVault,
+        treasuryVault,
+        systemProgram: SystemProgram.programId,
+        levelBonusTable: levelBonusTablePda,
+      })
+      .signers([staker])
+      .rpc();
+    const cfgFunded = await program.account.config.fetch(configPda);
+    expect(cfgFunded.stakingUndistributedXnt.toNumber()).to.be.greaterThan(0);
+
+    await sleep(1200);
+    await program.methods
+      .stakeMind(new BN(1))
+      .accounts({
+        owner: staker.publicKey,
+        config: configPda,
+        userProfile: profilePda(staker.publicKey),
+        userStake: stakePda(staker.publicKey),
+        vaultAuthority,
+        stakingMindVault,
+        ownerMindAta: userMindAta(staker.publicKey),
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([staker])
+      .rpc();
+
+    const cfgRolled = await program.account.config.fetch(configPda);
+    expect(cfgRolled.stakingRewardRateXntPerSec.toNumber()).to.be.greaterThan(0);
+    expect(cfgRolled.stakingEpochEndTs.toNumber()).to.be.greaterThan(
+      cfgFunded.stakingEpochEndTs.toNumber()
+    );
+
+    await program.methods
+      .adminSetAutoRollEpoch(new BN(0))
+      .accounts({ admin: admin.publicKey, config: configPda })
+      .signers([admin])
+      .rpc();
+  });
+
+  it("rejects emission above the sanity ceiling", async () => {
+    try {
+      await program.methods
+        .adminUpdateConfig(EMISSION_PER_SEC.addn(1), MAX_EFFECTIVE_HP, SECONDS_PER_DAY)
+        .accounts({ admin: admin.publicKey, config: configPda })
+        .signers([admin])
+        .rpc();
+      expect.fail("Expected emission above the ceiling to be rejected");
+    } catch (err) {
+      expect(`${err}`).to.include("EmissionTooHigh");
+    }
+  });
+
+  it("pays a partial XNT claim from an underfunded vault and carries the rest", async () => {
+    const staker = await newUser();
+    await buyContractFor(staker, 0, 0);
+    await sleep(1200);
+    await claimMindFor(staker, 0);
+    const minted = await getTokenAmount(userMindAta(staker.publicKey));
+    await stakeMindFor(staker, minted.divn(2));
+
+    await fundStakingVault(LAMPORTS_PER_SOL);
+    await program.methods
+      .rollEpoch(new BN(10))
+      .accounts({ admin: admin.publicKey, config: configPda, stakingRewardVault })
+      .rpc();
+    await sleep(2100);
+    // Moves the accrued reward into `reward_owed` before the vault is drained.
+    await stakeMindFor(staker, new BN(1));
+    const owedBefore = (await program.account.userStake.fetch(stakePda(staker.publicKey)))
+      .rewardOwed;
+    expect(owedBefore.toNumber()).to.be.greaterThan(1_000);
+
+    const rent = await provider.connection.getMinimumBalanceForRentExemption(9);
+    const vaultBalance = await provider.connection.getBalance(stakingRewardVault);
+    const leftInVault = 1_000;
+    await program.methods
+      .adminWithdrawStakingRewards(new BN(vaultBalance - rent - leftInVault))
+      .accounts({
+        admin: admin.publicKey,
+        config: configPda,
+        stakingRewardVault,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([admin])
+      .rpc();
+
+    const claimAccounts = {
+      owner: staker.publicKey,
+      config: configPda,
+      userProfile: profilePda(staker.publicKey),
+      userStake: stakePda(staker.publicKey),
+      stakingRewardVault,
+      systemProgram: SystemProgram.programId,
+    };
+    try {
+      await program.methods.claimXnt().accounts(claimAccounts).signers([staker]).rpc();
+      expect.fail("Expected full claim to fail on an underfunded vault");
+    } catch (err) {
+      expect(`${err}`).to.include("InsufficientVaultBalance");
+    }
+
+    const before = await getLamports(staker.publicKey);
+    await program.methods.claimXntPartial().accounts(claimAccounts).signers([staker]).rpc();
+    const after = await getLamports(staker.publicKey);
+    expect(after.sub(before).toNumber()).to.eq(leftInVault);
+
+    const stakeAfter = await program.account.userStake.fetch(stakePda(staker.publicKey));
+    expect(stakeAfter.rewardOwed.toNumber()).to.be.greaterThan(0);
+    expect(stakeAfter.rewardOwed.toNumber()).to.be.lessThan(owedBefore.toNumber());
+
+    await fundStakingVault(LAMPORTS_PER_SOL);
+    await program.methods
+      .rollEpoch(new BN(10))
+      .accounts({ admin: admin.publicKey, config: configPda, stakingRewardVault })
+      .rpc();
+    await program.methods.claimXntPartial().accounts(claimAccounts).signers([staker]).rpc();
+    const settled = await program.account.userStake.fetch(stakePda(staker.publicKey));
+    expect(settled.rewardOwed.toNumber()).to.eq(0);
+  });
+
+  it("rejects buff upgrades inside the configured cooldown", async () => {
+    await program.methods
+      .adminUpdateRigBuffConfig({
+        mindPerHpPerDay: MIND_PER_HP_PER_DAY,
+        minBuffUpgradeIntervalSec: new BN(3600),
+      })
+      .accounts({
+        admin: admin.publicKey,
+        config: configPda,
+        rigBuffConfig: rigBuffConfigPda,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([admin])
+      .rpc();
+
+    const user = await newUser();
+    await buyContractFor(user, 0, 0);
+    const positionKey = positionPda(user.publicKey, 0);
+    await warpForwardSeconds(5);
+    await claimMindFor(user, 0);
+
+    try {
+      await program.methods
+        .renewRigWithBuff()
+        .accounts({
+          owner: user.publicKey,
+          config: configPda,
+          rigBuffConfig: rigBuffConfigPda,
+          userProfile: profilePda(user.publicKey),
+          position: positionKey,
+          stakingRewardVault,
+          treasuryVault,
+          mindMint,
+          ownerMindAta: userMindAta(user.publicKey),
+          burnMindVault: mindBurnVault,
+          treasuryMindVault: mindTreasuryVault,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          levelBonusTable: levelBonusTablePda,
+        })
+        .signers([user])
+        .rpc();
+      expect.fail("Expected buff upgrade to fail inside cooldown");
+    } catch (err) {
+      expect(`${err}`).to.include("BuffUpgradeCooldown");
+    }
+
+    await program.methods
+      .adminUpdateRigBuffConfig({
+        mindPerHpPerDay: MIND_PER_HP_PER_DAY,
+        minBuffUpgradeIntervalSec: new BN(0),
+      })
+      .accounts({
+        admin: admin.publicKey,
+        config: configPda,
+        rigBuffConfig: rigBuffConfigPda,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([admin])
+      .rpc();
+  });
+
+  it("measures the buff cooldown from the last upgrade, not the cycle start", async () => {
+    const setCooldown = (seconds: number) =>
+      program.methods
+        .adminUpdateRigBuffConfig({
+          mindPerHpPerDay: MIND_PER_HP_PER_DAY,
+          minBuffUpgradeIntervalSec: new BN(seconds),
+        })
+        .accounts({
+          admin: admin.publicKey,
+          config: configPda,
+          rigBuffConfig: rigBuffConfigPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([admin])
+        .rpc();
+    const user = await newUser(100);
+    const positionKey = positionPda(user.publicKey, 0);
+    const renewWithBuff = () =>
+      program.methods
+        .renewRigWithBuff()
+        .accounts({
+          owner: user.publicKey,
+          config: configPda,
+          rigBuffConfig: rigBuffConfigPda,
+          userProfile: profilePda(user.publicKey),
+          position: positionKey,
+          stakingRewardVault,
+          treasuryVault,
+          mindMint,
+          ownerMindAta: userMindAta(user.publicKey),
+          burnMindVault: mindBurnVault,
+          treasuryMindVault: mindTreasuryVault,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          levelBonusTable: levelBonusTablePda,
+        })
+        .signers([user])
+        .rpc();
+
+    await buyContractFor(user, 1, 0);
+    const bought = await program.account.minerPosition.fetch(positionKey);
+    // Upgrade early in the renew window; the buff lands at the current end.
+    await warpForwardSeconds(12);
+    await claimMindFor(user, 0);
+    await renewWithBuff();
+    const upgraded = await program.account.minerPosition.fetch(positionKey);
+    expect(upgraded.buffLevel).to.eq(1);
+    expect(upgraded.lastBuffUpgradeTs.toNumber()).to.be.at.least(bought.endTs.toNumber());
+
+    await setCooldown(20);
+    try {
+      // Landing the buff clears the pending-buff timestamp but not the upgrade time.
+      await warpForwardSeconds(14);
+      await claimMindFor(user, 0);
+      const landed = await program.account.minerPosition.fetch(positionKey);
+      expect(landed.buffAppliedFromCycle.toNumber()).to.eq(0);
+      try {
+        await renewWithBuff();
+        expect.fail("Expected the next upgrade to wait out the cooldown");
+      } catch (err) {
+        expect(`${err}`).to.include("BuffUpgradeCooldown");
+      }
+    } finally {
+      await setCooldown(0);
+    }
+  });
+
+  it("never commits a staking epoch larger than the vault can cover", async () => {
+    const staker = await newUser();
+    await buyContractFor(staker, 0, 0);
+    await sleep(1200);
+    await claimMindFor(staker, 0);
+    const minted = await getTokenAmount(userMindAta(staker.publicKey));
+    await stakeMindFor(staker, minted.divn(2));
+
+    await fundStakingVault(LAMPORTS_PER_SOL);
+    await program.methods
+      .rollEpoch(new BN(10))
+      .accounts({ admin: admin.publicKey, config: configPda, stakingRewardVault })
+      .rpc();
+
+    const rent = await provider.connection.getMinimumBalanceForRentExemption(9);
+    const vaultBalance = await provider.connection.getBalance(stakingRewardVault);
+    const leftInVault = 5_000;
+    await program.methods
+      .adminWithdrawStakingRewards(new BN(vaultBalance - rent - leftInVault))
+      .accounts({
+        admin: admin.publicKey,
+        config: configPda,
+        stakingRewardVault,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([admin])
+      .rpc();
+
+    await program.methods
+      .rollEpoch(new BN(10))
+      .accounts({ admin: admin.publicKey, config: configPda, stakingRewardVault })
+      .rpc();
+    const cfg = await program.account.config.fetch(configPda);
+    expect(cfg.stakingRewardRateXntPerSec.muln(10).toNumber()).to.be.at.most(leftInVault);
+    expect(cfg.stakingAccountedBalance.toNumber()).to.be.at.most(leftInVault);
+  });
+
+  it("reports the next position index after racing buys", async () => {
+    const user = await newUser();
+    const results = await Promise.allSettled([
+      buyContractFor(user, 0, 0),
+      buyContractFor(user, 0, 0),
+    ]);
+    expect(results.filter((r) => r.status === "fulfilled").length).to.eq(1);
+
+    const { events } = await program.methods
+      .reservePositionIndex()
+      .accounts({ owner: user.publicKey, userProfile: profilePda(user.publicKey) })
+      .simulate();
+    const next = (events[0].data as any).nextPositionIndex.toNumber();
+    expect(next).to.eq(1);
+    expect((events[0].data as any).position.toBase58()).to.eq(
+      positionPda(user.publicKey, next).toBase58()
+    );
+
+    await buyContractFor(user, 0, next);
+    const profile = await program.account.userMiningProfile.fetch(profilePda(user.publicKey));
+    expect(profile.nextPositionIndex.toNumber()).to.eq(2);
+  });
+
+  it("forfeits unclaimed rewards of long-expired positions when enabled", async () => {
+    const setForfeit = async (enabled: boolean, windowSeconds: number) =>
+      program.methods
+        .adminSetForfeitExpiredRewards(enabled, new BN(windowSeconds))
+        .accounts({ admin: admin.publicKey, config: configPda })
+        .signers([admin])
+        .rpc();
+    try {
+      await program.methods
+        .adminSetForfeitExpiredRewards(true, new BN("9223372036854775808"))
+        .accounts({ admin: admin.publicKey, config: configPda })
+        .signers([admin])
+        .rpc();
+      expect.fail("Expected a window beyond i64 to be rejected");
+    } catch (err) {
+      expect(`${err}`).to.include("InvalidAmount");
+    }
+    await setForfeit(true, 1);
+
+    const user = await newUser();
+    await buyContractFor(user, 0, 0);
+    await warpForwardSeconds(12);
+    await program.methods
+      .deactivatePosition()
+      .accounts({
+        owner: user.publicKey,
+        config: configPda,
+        position: positionPda(user.publicKey, 0),
+        userProfile: profilePda(user.publicKey),
+        systemProgram: SystemProgram.programId,
+        levelBonusTable: levelBonusTablePda,
+      })
+      .signers([user])
+      .rpc();
+
+    try {
+      await claimMindFor(user, 0);
+      expect.fail("Expected forfeited rewards to be unclaimable");
+    } catch (err) {
+      expect(`${err}`).to.include("NothingToClaim");
+    } finally {
+      await setForfeit(false, 0);
+    }
+  });
+
+  it("lets only the admin repair position fields with validation", async () => {
+    const user = await newUser();
+    await buyContractFor(user, 1, 0);
+    const positionKey = positionPda(user.publicKey, 0);
+    const repair = (params: Record<string, unknown>, signer: Keypair) =>
+      program.methods
+        .adminRepairPosition({
+          rewardDebt: null,
+          finalAccMindPerHp: null,
+          expired: null,
+          deactivated: null,
+          buffLevel: null,
+          lastLevelApplied: null,
+          ...params,
+        } as any)
+        .accounts({
+          admin: signer.publicKey,
+          config: configPda,
+          position: positionKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([signer])
+        .rpc();
+
+    try {
+      await repair({ buffLevel: 1 }, user);
+      expect.fail("Expected non-admin repair to fail");
+    } catch (err) {
+      expect(`${err}`).to.match(/ConstraintHasOne|has one/i);
+    }
+
+    try {
+      await repair({ deactivated: true }, admin);
+      expect.fail("Expected deactivated without expired to be rejected");
+    } catch (err) {
+      expect(`${err}`).to.include("InvalidRepairParams");
+    }
+
+    await repair({ buffLevel: 2 }, admin);
+    const position = await program.account.minerPosition.fetch(positionKey);
+    expect(position.buffLevel).to.eq(2);
+    expect(position.expired).to.eq(false);
+  });
+
+  it("routes unstaking through request + complete when a cooldown is set", async () => {
+    const setCooldown = async (seconds: number) =>
+      program.methods
+        .adminSetUnstakeCooldown(new BN(seconds))
+        .accounts({ admin: admin.publicKey, config: configPda })
+        .signers([admin])
+        .rpc();
+    const staker = await newUser();
+    await buyContractFor(staker, 0, 0);
+    await sleep(1200);
+    await claimMindFor(staker, 0);
+    const minted = await getTokenAmount(userMindAta(staker.publicKey));
+    const stakeAmount = minted.divn(2);
+    await stakeMindFor(staker, stakeAmount);
+
+    await setCooldown(5);
+    try {
+      try {
+        await program.methods
+          .unstakeMind(stakeAmount)
+          .accounts({
+            owner: staker.publicKey,
+            config: configPda,
+            userStake: stakePda(staker.publicKey),
+            vaultAuthority,
+            stakingMindVault,
+            mindMint,
+            ownerMindAta: userMindAta(staker.publicKey),
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([staker])
+          .rpc();
+        expect.fail("Expected single-step unstake to be disabled");
+      } catch (err) {
+        expect(`${err}`).to.include("UnstakeCooldownEnabled");
+      }
+
+      await program.methods
+        .requestUnstake(stakeAmount)
+        .accounts({
+          owner: staker.publicKey,
+          config: configPda,
+          userStake: stakePda(staker.publicKey),
+          vaultAuthority,
+          stakingMindVault,
+          mindMint,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([staker])
+        .rpc();
+      const requested = await program.account.userStake.fetch(stakePda(staker.publicKey));
+      const expectedPayout = stakeAmount.sub(stakeAmount.muln(600).divn(10_000));
+      expect(requested.pendingUnstake.toString()).to.eq(expectedPayout.toString());
+      expect(requested.stakedMind.toNumber()).to.eq(0);
+
+      const completeAccounts = {
+        owner: staker.publicKey,
+        config: configPda,
+        userStake: stakePda(staker.publicKey),
+        vaultAuthority,
+        stakingMindVault,
+        ownerMindAta: userMindAta(staker.publicKey),
+        tokenProgram: TOKEN_PROGRAM_ID,
+      };
+      try {
+        await program.methods.completeUnstake().accounts(completeAccounts).signers([staker]).rpc();
+        expect.fail("Expected complete_unstake to wait for the cooldown");
+      } catch (err) {
+        expect(`${err}`).to.include("UnstakeCooldownActive");
+      }
+
+      const before = await getTokenAmount(userMindAta(staker.publicKey));
+      await warpForwardSeconds(6);
+      await program.methods.completeUnstake().accounts(completeAccounts).signers([staker]).rpc();
+      const after = await getTokenAmount(userMindAta(staker.publicKey));
+      expect(after.sub(before).toString()).to.eq(expectedPayout.toString());
+      const completed = await program.account.userStake.fetch(stakePda(staker.publicKey));
+      expect(completed.pendingUnstake.toNumber()).to.eq(0);
+    } finally {
+      await setCooldown(0);
+    }
+  });
+
+  it("sweeps only the treasury excess above the configured threshold", async () => {
+    const destination = Keypair.generate();
+    await airdrop(destination.publicKey, 1);
+    const setSweep = async (threshold: BN, target: PublicKey) =>
+      program.methods
+        .adminSetTreasurySweep(threshold, target)
+        .accounts({ admin: admin.publicKey, config: configPda })
+        .signers([admin])
+        .rpc();
+    const sweep = () =>
+      program.methods
+        .sweepTreasury()
+        .accounts({
+          config: configPda,
+          treasuryVault,
+          treasuryDestination: destination.publicKey,
+        })
+        .rpc();
+
+    const rent = await provider.connection.getMinimumBalanceForRentExemption(9);
+    const available = (await provider.connection.getBalance(treasuryVault)) - rent;
+    const threshold = new BN(available + LAMPORTS_PER_SOL);
+    await setSweep(threshold, destination.publicKey);
+    try {
+      try {
+        await sweep();
+        expect.fail("Expected sweep below threshold to fail");
+      } catch (err) {
+        expect(`${err}`).to.include("TreasuryBelowThreshold");
+      }
+
+      const excess = 250_000_000;
+      const fundTx = new Transaction().add(
+        SystemProgram.transfer({
+          fromPubkey: admin.publicKey,
+          toPubkey: treasuryVault,
+          lamports: LAMPORTS_PER_SOL + excess,
+        })
+      );
+      await provider.sendAndConfirm(fundTx, [admin]);
+
+      const before = await getLamports(destination.publicKey);
+      await sweep();
+      const after = await getLamports(destination.publicKey);
+      expect(after.sub(before).toNumber()).to.eq(excess);
+
+      try {
+        await sweep();
+        expect.fail("Expected sweep at threshold to fail");
+      } catch (err) {
+        expect(`${err}`).to.include("TreasuryBelowThreshold");
+      }
+    } finally {
+      await setSweep(new BN(0), PublicKey.default);
+    }
+  });
+
+  it("updates seconds_per_day for new buys and rejects zero", async () => {
+    const setSecondsPerDay = async (value: BN) =>
+      program.methods
+        .adminSetSecondsPerDay(value)
+        .accounts({ admin: admin.publicKey, config: configPda })
+        .signers([admin])
+        .rpc();
+
+    try {
+      await setSecondsPerDay(new BN(0));
+      expect.fail("Expected zero seconds_per_day to be rejected");
+    } catch (err) {
+      expect(`${err}`).to.include("SecondsPerDayOutOfRange");
+    }
+
+    const user = await newUser();
+    await setSecondsPerDay(new BN(2));
+    try {
+      await buyContractFor(user, 0, 0);
+      const position = await program.account.minerPosition.fetch(positionPda(user.publicKey, 0));
+      expect(position.endTs.sub(position.startTs).toNumber()).to.eq(14);
+    } finally {
+      await setSecondsPerDay(SECONDS_PER_DAY);
+    }
+  });
+
+  it("pays staking rewards from an SPL vault in SPL mode and switches back to native", async () => {
+    const staker = await newUser();
+    await buyContractFor(staker, 0, 0);
+    await sleep(1200);
+    await claimMindFor(staker, 0);
+    const minted = await getTokenAmount(userMindAta(staker.publicKey));
+    await stakeMindFor(staker, minted.divn(2));
+
+    const xntMint = await createMint(provider.connection, admin, admin.publicKey, null, 9);
+    const stakingXntVault = await createAccount(
+      provider.connection,
+      admin,
+      xntMint,
+      vaultAuthority,
+      Keypair.generate()
+    );
+    const stakerXntAta = await createAssociatedTokenAccountIdempotent(
+      provider.connection,
+      admin,
+      xntMint,
+      staker.publicKey
+    );
+
+    await program.methods
+      .adminUseSplXnt()
+      .accounts({
+        admin: admin.publicKey,
+        config: configPda,
+        vaultAuthority,
+        xntMint,
+        stakingXntVault,
+      })
+      .signers([admin])
+      .rpc();
+    try {
+      await mintTo(provider.connection, admin, xntMint, stakingXntVault, admin, 1_000_000_000);
+      await program.methods
+        .rollEpoch(new BN(10))
+        .accounts({ admin: admin.publicKey, config: configPda, stakingRewardVault })
+        .remainingAccounts([{ pubkey: stakingXntVault, isWritable: false, isSigner: false }])
+        .rpc();
+      const cfg = await program.account.config.fetch(configPda);
+      expect(cfg.stakingRewardRateXntPerSec.toNumber()).to.be.greaterThan(0);
+
+      await sleep(2100);
+      const lamportsBefore = await getLamports(staker.publicKey);
+      await program.methods
+        .claimXnt()
+        .accounts({
+          owner: staker.publicKey,
+          config: configPda,
+          userProfile: profilePda(staker.publicKey),
+          userStake: stakePda(staker.publicKey),
+          stakingRewardVault,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts([
+          { pubkey: stakingXntVault, isWritable: true, isSigner: false },
+          { pubkey: stakerXntAta, isWritable: true, isSigner: false },
+          { pubkey: vaultAuthority, isWritable: false, isSigner: false },
+          { pubkey: TOKEN_PROGRAM_ID, isWritable: false, isSigner: false },
+        ])
+        .signers([staker])
+        .rpc();
+      expect((await getTokenAmount(stakerXntAta)).toNumber()).to.be.greaterThan(0);
+      const lamportsAfter = await getLamports(staker.publicKey);
+      expect(lamportsAfter.lte(lamportsBefore)).to.eq(true);
+    } finally {
+      await program.methods
+        .adminUseNativeXnt()
+        .accounts({
+          admin: admin.publicKey,
+          config: configPda,
+          stakingRewardVault,
+          treasuryVault,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([admin])
+        .rpc();
+    }
+    const cfgAfter = await program.account.config.fetch(configPda);
+    expect(cfgAfter.xntMint.toBase58()).to.eq(SystemProgram.programId.toBase58());
+  });
+
+  it("leaves an already-current config untouched on migrate", async () => {
+    const before = await program.account.config.fetch(configPda);
+    const sizeBefore = (await provider.connection.getAccountInfo(configPda))!.data.length;
+    await program.methods
+      .adminMigrateConfig()
+      .accounts({
+        admin: admin.publicKey,
+        config: configPda,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([admin])
+      .rpc();
+    const after = await program.account.config.fetch(configPda);
+    expect((await provider.connection.getAccountInfo(configPda))!.data.length).to.eq(sizeBefore);
+    expect(after.admin.toBase58()).to.eq(before.admin.toBase58());
+    expect(after.emissionPerSec.toString()).to.eq(before.emissionPerSec.toString());
+    expect(after.stakingTotalStakedMind.toString()).to.eq(
+      before.stakingTotalStakedMind.toString()
+    );
+  });
+
+  it("probes which position indices exist for an owner", async () => {
+    const user = await newUser();
+    await buyContractFor(user, 0, 0);
+    await buyContractFor(user, 0, 1);
+
+    const { events } = await program.methods
+      .probePositions(new BN(0), new BN(4))
+      .accounts({ owner: user.publicKey })
+      .remainingAccounts(
+        [0, 1, 2, 3].map((index) => ({
+          pubkey: positionPda(user.publicKey, index),
+          isWritable: false,
+          isSigner: false,
+        }))
+      )
+      .simulate();
+    const data = events[0].data as any;
+    expect(data.bitmap.toNumber()).to.eq(0b0011);
+    expect(data.activeBitmap.toNumber()).to.eq(0b0011);
+  });
+
+  it("reports HP and emission overflows with subsystem-specific errors", async () => {
+    const user = await newUser();
+    await buyContractFor(user, 0, 0);
+    const setNetworkHp = async (value: BN) =>
+      program.methods
+        .adminSetNetworkHpActive(value)
+        .accounts({ admin: admin.publicKey, config: configPda })
+        .signers([admin])
+        .rpc();
+
+    await warpForwardSeconds(12);
+    const networkHp = (await program.account.config.fetch(configPda)).networkHpActive;
+    await setNetworkHp(new BN(0));
+    try {
+      await program.methods
+        .deactivatePosition()
+        .accounts({
+          owner: user.publicKey,
+          config: configPda,
+          position: positionPda(user.publicKey, 0),
+          userProfile: profilePda(user.publicKey),
+          systemProgram: SystemProgram.programId,
+          levelBonusTable: levelBonusTablePda,
+        })
+        .signers([user])
+        .rpc();
+      expect.fail("Expected HP underflow on expiry");
+    } catch (err) {
+      expect(`${err}`).to.include("HpMathOverflow");
+    } finally {
+      await setNetworkHp(networkHp);
+    }
+
+    const fixAccumulator = async (value: BN) =>
+      program.methods
+        .adminFixAccumulator(value)
+        .accounts({
+          admin: admin.publicKey,
+          config: configPda,
+          userProfile: profilePda(user.publicKey),
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([admin])
+        .rpc();
+    const acc = (await program.account.config.fetch(configPda)).accMindPerHp;
+    await fixAccumulator(new BN(2).pow(new BN(127)));
+    try {
+      await sleep(1100);
+      await claimMindFor(user, 0);
+      expect.fail("Expected accumulator overflow");
+    } catch (err) {
+      expect(`${err}`).to.include("EmissionMathOverflow");
+    } finally {
+      await fixAccumulator(acc);
+    }
+  });
+
+  it("emits PositionExpired when claim_mind auto-expires a rig", async () => {
+    const user = await newUser();
+    await buyContractFor(user, 0, 0);
+    await warpForwardSeconds(12);
+
+    const { events } = await program.methods
+      .claimMind()
+      .accounts({
+        owner: user.publicKey,
+        config: configPda,
+        userProfile: profilePda(user.publicKey),
+        position: positionPda(user.publicKey, 0),
+        vaultAuthority,
+        mindMint,
+        userMindAta: userMindAta(user.publicKey),
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        levelBonusTable: levelBonusTablePda,
+      })
+      .signers([user])
+      .simulate();
+    const expired = events.find((e) => e.name === "PositionExpired");
+    expect(expired).to.not.be.undefined;
+    const data = expired!.data as any;
+    expect(data.owner.toBase58()).to.eq(user.publicKey.toBase58());
+    expect(data.position.toBase58()).to.eq(positionPda(user.publicKey, 0).toBase58());
+    expect(data.hpEffectiveRemoved.toNumber()).to.be.greaterThan(0);
+  });
+
+  it("caps consecutive grace-period renewals when configured", async () => {
+    const setMaxRenews = async (value: number) =>
+      program.methods
+        .adminSetMaxConsecutiveRenews(value)
+        .accounts({ admin: admin.publicKey, config: configPda })
+        .signers([admin])
+        .rpc();
+    const user = await newUser();
+    await buyContractFor(user, 0, 0);
+    const renew = async () =>
+      program.methods
+        .renewRig()
+        .accounts({
+          owner: user.publicKey,
+          config: configPda,
+          userProfile: profilePda(user.publicKey),
+          position: positionPda(user.publicKey, 0),
+          stakingRewardVault,
+          treasuryVault,
+          systemProgram: SystemProgram.programId,
+          levelBonusTable: levelBonusTablePda,
+        })
+        .signers([user])
+        .rpc();
+
+    await setMaxRenews(1);
+    try {
+      await warpForwardSeconds(8);
+      await renew();
+      const position = await program.account.minerPosition.fetch(
+        positionPda(user.publicKey, 0)
+      );
+      expect(position.consecutiveRenews).to.eq(1);
+
+      await warpForwardSeconds(8);
+      try {
+        await renew();
+        expect.fail("Expected renew cap to block the second renewal");
+      } catch (err) {
+        expect(`${err}`).to.include("RenewCapReached");
+      }
+    } finally {
+      await setMaxRenews(0);
+    }
+  });
+
+  it("reports contract terms and rejects unknown contract types", async () => {
+    const { events } = await program.methods
+      .getContractTerms(1)
+      .accounts({ config: configPda })
+      .simulate();
+    const data = events[0].data as any;
+    expect(data.durationDays.toNumber()).to.eq(14);
+    expect(data.baseHpScaled.toNumber()).to.eq(800);
+
+    try {
+      await program.methods.getContractTerms(7).accounts({ config: configPda }).simulate();
+      expect.fail("Expected invalid contract type");
+    } catch (err) {
+      expect(`${err}`).to.include("InvalidContractType");
+    }
+  });
+
+  it("splits MIND emission between miners and stakers", async () => {
+    const setStakerBps = async (bps: number) =>
+      program.methods
+        .adminSetStakerEmissionBps(bps)
+        .accounts({ admin: admin.publicKey, config: configPda })
+        .signers([admin])
+        .rpc();
+    try {
+      await setStakerBps(10_001);
+      expect.fail("Expected staker share above 100% to fail");
+    } catch (err) {
+      expect(`${err}`).to.include("InvalidStakerEmissionBps");
+    }
+
+    const staker = await newUser();
+    await buyContractFor(staker, 0, 0);
+    await sleep(1200);
+    await claimMindFor(staker, 0);
+    const minted = await getTokenAmount(userMindAta(staker.publicKey));
+    await stakeMindFor(staker, minted.divn(2));
+
+    await setStakerBps(5_000);
+    try {
+      const before = await program.account.config.fetch(configPda);
+      const stakeBefore = await program.account.userStake.fetch(stakePda(staker.publicKey));
+      const balanceBefore = await getTokenAmount(userMindAta(staker.publicKey));
+      await sleep(2200);
+
+      await program.methods
+        .claimStakedMind()
+        .accounts({
+          owner: staker.publicKey,
+          config: configPda,
+          userStake: stakePda(staker.publicKey),
+          vaultAuthority,
+          mindMint,
+          ownerMindAta: userMindAta(staker.publicKey),
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([staker])
+        .rpc();
+
+      const after = await program.account.config.fetch(configPda);
+      const scale = new BN(10).pow(new BN(18));
+      const stakerDelta = after.stakingAccMindPerMind.sub(before.stakingAccMindPerMind);
+      const minerDelta = after.accMindPerHp.sub(before.accMindPerHp);
+      expect(stakerDelta.gtn(0)).to.eq(true);
+      const toStakers = stakerDelta.mul(after.stakingTotalStakedMind).div(scale);
+      const toMiners = minerDelta.mul(after.networkHpActive).div(scale);
+      expect(toStakers.sub(toMiners).abs().lte(toStakers.divn(100))).to.eq(true);
+
+      const expected = stakeBefore.stakedMind
+        .mul(after.stakingAccMindPerMind)
+        .div(scale)
+        .sub(stakeBefore.mindRewardDebt);
+      const balanceAfter = await getTokenAmount(userMindAta(staker.publicKey));
+      expect(balanceAfter.sub(balanceBefore).toString()).to.eq(expected.toString());
+    } finally {
+      await setStakerBps(0);
+    }
+  });
+
+  it("lets allowlisted keepers roll epochs", async () => {
+    const keeper = await newUser();
+    const setRollers = async (rollers: PublicKey[]) =>
+      program.methods
+        .adminSetEpochRollers(rollers)
+        .accounts({ admin: admin.publicKey, config: configPda })
+        .signers([admin])
+        .rpc();
+    const rollAs = async (signer: Keypair) =>
+      program.methods
+        .rollEpoch(new BN(10))
+        .accounts({ admin: signer.publicKey, config: configPda, stakingRewardVault })
+        .signers([signer])
+        .rpc();
+
+    try {
+      await rollAs(keeper);
+      expect.fail("Expected non-allowlisted caller to be rejected");
+    } catch (err) {
+      expect(`${err}`).to.include("Unauthorized");
+    }
+
+    await setRollers([keeper.publicKey]);
+    try {
+      await rollAs(keeper);
+      const outsider = await newUser();
+      try {
+        await rollAs(outsider);
+        expect.fail("Expected caller outside the allowlist to be rejected");
+      } catch (err) {
+        expect(`${err}`).to.include("Unauthorized");
+      }
+    } finally {
+      await setRollers([]);
+    }
+  });
+
+  it("previews claim_mind across positions without minting", async () => {
+    const user = await newUser();
+    await buyContractFor(user, 0, 0);
+    await buyContractFor(user, 0, 1);
+    await warpForwardSeconds(8);
+    const balanceBefore = await getTokenAmount(userMindAta(user.publicKey));
+
+    const positions = [positionPda(user.publicKey, 0), positionPda(user.publicKey, 1)];
+    const { events } = await program.methods
+      .simulateClaimAll()
+      .accounts({
+        owner: user.publicKey,
+        config: configPda,
+        userProfile: profilePda(user.publicKey),
+        levelBonusTable: levelBonusTablePda,
+      })
+      .remainingAccounts(
+        positions.map((pubkey) => ({ pubkey, isWritable: false, isSigner: false }))
+      )
+      .simulate();
+
+    const perPosition = events.filter((e) => e.name === "SimulatedClaim");
+    expect(perPosition.length).to.eq(2);
+    perPosition.forEach((e) => expect((e.data as any).expired).to.eq(true));
+    const sum = perPosition.reduce(
+      (acc, e) => acc.add((e.data as any).pending),
+      new BN(0)
+    );
+    const total = events.find((e) => e.name === "SimulatedClaimTotal")!.data as any;
+    expect(total.positions).to.eq(2);
+    expect(total.totalPending.toString()).to.eq(sum.toString());
+    expect(sum.gtn(0)).to.eq(true);
+
+    const balanceAfter = await getTokenAmount(userMindAta(user.publicKey));
+    expect(balanceAfter.toString()).to.eq(balanceBefore.toString());
+  });
+
+  it("mints large pending rewards in capped chunks", async () => {
+    const setMaxMint = async (value: BN) =>
+      program.methods
+        .adminSetMaxMintPerClaim(value)
+        .accounts({ admin: admin.publicKey, config: configPda })
+        .signers([admin])
+        .rpc();
+    const user = await newUser();
+    await buyContractFor(user, 0, 0);
+    await warpForwardSeconds(8);
+
+    const chunk = new BN(1_000);
+    await setMaxMint(chunk);
+    try {
+      const start = await getTokenAmount(userMindAta(user.publicKey));
+      await claimMindFor(user, 0);
+      const afterFirst = await getTokenAmount(userMindAta(user.publicKey));
+      expect(afterFirst.sub(start).toString()).to.eq(chunk.toString());
+
+      await claimMindFor(user, 0);
+      const afterSecond = await getTokenAmount(userMindAta(user.publicKey));
+      expect(afterSecond.sub(afterFirst).toString()).to.eq(chunk.toString());
+    } finally {
+      await setMaxMint(new BN("18446744073709551615"));
+    }
+
+    const beforeRest = await getTokenAmount(userMindAta(user.publicKey));
+    await claimMindFor(user, 0);
+    const afterRest = await getTokenAmount(userMindAta(user.publicKey));
+    expect(afterRest.sub(beforeRest).gt(chunk)).to.eq(true);
+  });
+
+  it("keeps mining running while staking is paused", async () => {
+    const setStakingPaused = async (paused: boolean) =>
+      program.methods
+        .adminSetStakingPaused(paused)
+        .accounts({ admin: admin.publicKey, config: configPda })
+        .signers([admin])
+        .rpc();
+    const user = await newUser();
+    await buyContractFor(user, 0, 0);
+    await sleep(1200);
+
+    await setStakingPaused(true);
+    try {
+      const before = await getTokenAmount(userMindAta(user.publicKey));
+      await claimMindFor(user, 0);
+      const minted = (await getTokenAmount(userMindAta(user.publicKey))).sub(before);
+      expect(minted.gtn(0)).to.eq(true);
+
+      try {
+        await stakeMindFor(user, minted.divn(2));
+        expect.fail("Expected staking to be paused");
+      } catch (err) {
+        expect(`${err}`).to.include("StakingPaused");
+      }
+      try {
+        await program.methods
+          .rollEpoch(new BN(10))
+          .accounts({ admin: admin.publicKey, config: configPda, stakingRewardVault })
+          .signers([admin])
+          .rpc();
+        expect.fail("Expected epoch roll to be paused");
+      } catch (err) {
+        expect(`${err}`).to.include("StakingPaused");
+      }
+    } finally {
+      await setStakingPaused(false);
+    }
+  });
+
+  it("claims several positions with one mint", async () => {
+    const user = await newUser();
+    await buyContractFor(user, 0, 0);
+    await buyContractFor(user, 0, 1);
+    await warpForwardSeconds(8);
+    const batchClaim = (positions: PublicKey[]) =>
+      program.methods
+        .batchClaimMind()
+        .accounts({
+          owner: user.publicKey,
+          config: configPda,
+          userProfile: profilePda(user.publicKey),
+          vaultAuthority,
+          mindMint,
+          userMindAta: userMindAta(user.publicKey),
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          levelBonusTable: levelBonusTablePda,
+        })
+        .remainingAccounts(
+          positions.map((pubkey) => ({ pubkey, isWritable: true, isSigner: false }))
+        )
+        .signers([user]);
+
+    const positions = [positionPda(user.publicKey, 0), positionPda(user.publicKey, 1)];
+    try {
+      await batchClaim(Array(9).fill(positions[0])).rpc();
+      expect.fail("Expected batch above the position cap to fail");
+    } catch (err) {
+      expect(`${err}`).to.include("TooManyPositions");
+    }
+
+    const before = await getTokenAmount(userMindAta(user.publicKey));
+    const { events } = await batchClaim(positions).simulate();
+    const simulated = events.find((e) => e.name === "BatchMindClaimed")!.data as any;
+    expect(simulated.positionCount).to.eq(2);
+    await batchClaim(positions).rpc();
+    const minted = (await getTokenAmount(userMindAta(user.publicKey))).sub(before);
+    expect(minted.gtn(0)).to.eq(true);
+
+    for (const index of [0, 1]) {
+      try {
+        await claimMindFor(user, index);
+        expect.fail("Expected expired positions to be fully claimed");
+      } catch (err) {
+        expect(`${err}`).to.include("NothingToClaim");
+      }
+    }
+  });
+
+  it("settles reward debt with the configured rounding so claims never drift", async () => {
+    const setRounding = async (halfUp: boolean) =>
+      program.methods
+        .adminSetRewardRounding(halfUp)
+        .accounts({ admin: admin.publicKey, config: configPda })
+        .signers([admin])
+        .rpc();
+    const user = await newUser();
+    await buyContractFor(user, 2, 0);
+    const position = positionPda(user.publicKey, 0);
+    const { events } = await program.methods
+      .simulateClaimMind()
+      .accounts({
+        owner: user.publicKey,
+        config: configPda,
+        userProfile: profilePda(user.publicKey),
+        position,
+        levelBonusTable: levelBonusTablePda,
+      })
+      .simulate();
+    const hp = (events.find((e) => e.name === "SimulatedClaim")!.data as any)
+      .hpEffective as BN;
+    const halfScale = ACC_SCALE.divn(2);
+
+    for (const halfUp of [false, true]) {
+      await setRounding(halfUp);
+      try {
+        const debtStart = (await program.account.minerPosition.fetch(position)).rewardDebt;
+        const balanceStart = await getTokenAmount(userMindAta(user.publicKey));
+        let prev: { exact: BN; debt: BN } | null = null;
+        for (let i = 0; i < 5; i++) {
+          await sleep(1100);
+          await claimMindFor(user, 0);
+          const acc = (await program.account.config.fetch(configPda)).accMindPerHp;
+          const debt = (await program.account.minerPosition.fetch(position)).rewardDebt;
+          // Cumulative drift of the paid total against the unrounded hp * acc / ACC_SCALE.
+          const exact = hp.mul(acc);
+          const drift = exact.sub(debt.mul(ACC_SCALE));
+          if (halfUp) {
+            expect(drift.abs().lte(halfScale)).to.eq(true);
+          } else {
+            expect(drift.gten(0)).to.eq(true);
+            expect(drift.lt(ACC_SCALE)).to.eq(true);
+          }
+          if (prev) {
+            const settleDrift = exact.sub(prev.exact).sub(debt.sub(prev.debt).mul(ACC_SCALE));
+            expect(settleDrift.abs().lt(ACC_SCALE)).to.eq(true);
+          }
+          prev = { exact, debt };
+        }
+        const debtEnd = (await program.account.minerPosition.fetch(position)).rewardDebt;
+        const minted = (await getTokenAmount(userMindAta(user.publicKey))).sub(balanceStart);
+        expect(minted.toString()).to.eq(debtEnd.sub(debtStart).toString());
+      } finally {
+        await setRounding(false);
+      }
+    }
+  });
+
+  it("rotates the admin through a two-step transfer", async () => {
+    const [pendingAdminPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("pending_admin")],
+      program.programId
+    );
+    const candidate = await newUser();
+    const propose = (from: Keypair, to: PublicKey) =>
+      program.methods
+        .adminProposeTransfer(to)
+        .accounts({
+          admin: from.publicKey,
+          config: configPda,
+          pendingAdmin: pendingAdminPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([from])
+        .rpc();
+    const accept = (signer: Keypair) =>
+      program.methods
+        .adminAcceptTransfer()
+        .accounts({ newAdmin: signer.publicKey, config: configPda, pendingAdmin: pendingAdminPda })
+        .signers([signer])
+        .rpc();
+
+    await propose(admin, candidate.publicKey);
+    await program.methods
+      .adminCancelTransfer()
+      .accounts({ admin: admin.publicKey, config: configPda, pendingAdmin: pendingAdminPda })
+      .signers([admin])
+      .rpc();
+    expect(await provider.connection.getAccountInfo(pendingAdminPda)).to.eq(null);
+
+    await propose(admin, candidate.publicKey);
+    const outsider = await newUser();
+    try {
+      await accept(outsider);
+      expect.fail("Expected only the candidate to accept");
+    } catch (err) {
+      expect(`${err}`).to.include("Unauthorized");
+    }
+    await accept(candidate);
+    let cfg = await program.account.config.fetch(configPda);
+    expect(cfg.admin.toBase58()).to.eq(candidate.publicKey.toBase58());
+    expect(await provider.connection.getAccountInfo(pendingAdminPda)).to.eq(null);
+
+    // Instructions reading the level and rig buff configs follow the new admin.
+    const leveler = await newUser();
+    await buyContractFor(leveler, 0, 0);
+    try {
+      await program.methods
+        .levelUp()
+        .accounts({
+          owner: leveler.publicKey,
+          config: configPda,
+          levelConfig: levelConfigPda,
+          userProfile: profilePda(leveler.publicKey),
+          ownerMindAta: userMindAta(leveler.publicKey),
+          burnMindVault: mindBurnVault,
+          treasuryMindVault: mindTreasuryVault,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          levelBonusTable: levelBonusTablePda,
+        })
+        .signers([leveler])
+        .rpc();
+      expect.fail("Expected level up to fail without XP");
+    } catch (err) {
+      expect(`${err}`).to.include("Insufficient XP");
+    }
+    const updateRigBuffs = (signer: Keypair) =>
+      program.methods
+        .adminUpdateRigBuffConfig({
+          mindPerHpPerDay: MIND_PER_HP_PER_DAY,
+          minBuffUpgradeIntervalSec: new BN(0),
+        })
+        .accounts({
+          admin: signer.publicKey,
+          config: configPda,
+          rigBuffConfig: rigBuffConfigPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([signer])
+        .rpc();
+    try {
+      await updateRigBuffs(admin);
+      expect.fail("Expected the previous admin to be locked out");
+    } catch (err) {
+      expect(`${err}`).to.include("Unauthorized");
+    }
+    await updateRigBuffs(candidate);
+    const buffs = await program.account.rigBuffConfig.fetch(rigBuffConfigPda);
+    expect(buffs.admin.toBase58()).to.eq(candidate.publicKey.toBase58());
+
+    await propose(candidate, admin.publicKey);
+    await accept(admin);
+    cfg = await program.account.config.fetch(configPda);
+    expect(cfg.admin.toBase58()).to.eq(admin.publicKey.toBase58());
+    await updateRigBuffs(admin);
+  });
+
+  it("halts user instructions and emission while paused", async () => {
+    const user = await newUser();
+    await buyContractFor(user, 0, 0);
+    const pause = () =>
+      program.methods
+        .adminPause()
+        .accounts({ admin: admin.publicKey, config: configPda })
+        .signers([admin])
+        .rpc();
+    const unpause = () =>
+      program.methods
+        .adminUnpause()
+        .accounts({ admin: admin.publicKey, config: configPda })
+        .signers([admin])
+        .rpc();
+
+    await pause();
+    let accAtPause: BN;
+    try {
+      accAtPause = (await program.account.config.fetch(configPda)).accMindPerHp;
+      await sleep(1200);
+      try {
+        await claimMindFor(user, 0);
+        expect.fail("Expected claim to be blocked while paused");
+      } catch (err) {
+        expect(`${err}`).to.include("ProtocolPaused");
+      }
+      try {
+        await buyContractFor(user, 0, 1);
+        expect.fail("Expected buy to be blocked while paused");
+      } catch (err) {
+        expect(`${err}`).to.include("ProtocolPaused");
+      }
+    } finally {
+      await unpause();
+    }
+
+    const cfg = await program.account.config.fetch(configPda);
+    expect(cfg.accMindPerHp.toString()).to.eq(accAtPause!.toString());
+    await sleep(1200);
+    await claimMindFor(user, 0);
+  });
+
+  it("lets anyone crank a new staking epoch once the last one has ended", async () => {
+    const staker = await newUser();
+    await buyContractFor(staker, 0, 0);
+    await sleep(1200);
+    await claimMindFor(staker, 0);
+    await stakeMindFor(staker, (await getTokenAmount(userMindAta(staker.publicKey))).divn(2));
+
+    await fundStakingVault(LAMPORTS_PER_SOL);
+    await program.methods
+      .rollEpoch(new BN(10))
+      .accounts({ admin: admin.publicKey, config: configPda, stakingRewardVault })
+      .rpc();
+    const seqAfterRoll = (await program.account.config.fetch(configPda)).epochSeq;
+
+    const cranker = await newUser();
+    const crank = () =>
+      program.methods
+        .crankEpoch()
+        .accounts({ caller: cranker.publicKey, config: configPda, stakingRewardVault })
+        .signers([cranker])
+        .rpc();
+    try {
+      await crank();
+      expect.fail("Expected crank to wait for the epoch to end");
+    } catch (err) {
+      expect(`${err}`).to.include("EpochNotEnded");
+    }
+
+    await warpForwardSeconds(11);
+    await fundStakingVault(LAMPORTS_PER_SOL);
+    await crank();
+    const cfg = await program.account.config.fetch(configPda);
+    expect(cfg.epochSeq.toString()).to.eq(seqAfterRoll.addn(1).toString());
+    expect(cfg.stakingRewardRateXntPerSec.toNumber()).to.be.greaterThan(0);
+    expect(cfg.stakingEpochEndTs.sub(cfg.stakingLastUpdateTs).toNumber()).to.eq(
+      SECONDS_PER_DAY.toNumber() * 7
+    );
+  });
+
+  it("keeps the epoch auto-roll started when the crank runs on top of it", async () => {
+    const setAutoRoll = (seconds: number) =>
+      program.methods
+        .adminSetAutoRollEpoch(new BN(seconds))
+        .accounts({ admin: admin.publicKey, config: configPda })
+        .signers([admin])
+        .rpc();
+    const staker = await newUser();
+    await buyContractFor(staker, 0, 0);
+    await sleep(1200);
+    await claimMindFor(staker, 0);
+    await stakeMindFor(staker, (await getTokenAmount(userMindAta(staker.publicKey))).divn(2));
+
+    await fundStakingVault(LAMPORTS_PER_SOL);
+    await program.methods
+      .rollEpoch(new BN(2))
+      .accounts({ admin: admin.publicKey, config: configPda, stakingRewardVault })
+      .rpc();
+    await setAutoRoll(5);
+    try {
+      // The purchase books new XNT into `staking_undistributed_xnt` for the auto-roll to commit.
+      await buyContractFor(staker, 0, 1);
+      const seqBefore = (await program.account.config.fetch(configPda)).epochSeq;
+      await warpForwardSeconds(3);
+
+      const cranker = await newUser();
+      await program.methods
+        .crankEpoch()
+        .accounts({ caller: cranker.publicKey, config: configPda, stakingRewardVault })
+        .signers([cranker])
+        .rpc();
+      const cfg = await program.account.config.fetch(configPda);
+      expect(cfg.epochSeq.toString()).to.eq(seqBefore.addn(1).toString());
+      expect(cfg.stakingRewardRateXntPerSec.toNumber()).to.be.greaterThan(0);
+      expect(cfg.stakingEpochEndTs.sub(cfg.stakingLastUpdateTs).toNumber()).to.eq(5);
+    } finally {
+      await setAutoRoll(0);
+    }
+  });
+
+  it("reads level bonuses from the on-chain table once it exists", async () => {
+    const defaults = [0, 0, 160, 340, 550, 780, 1000];
+    const initTable = (bonuses: number[]) =>
+      program.methods
+        .adminInitLevelBonusTable(bonuses)
+        .accounts({
+          admin: admin.publicKey,
+          config: configPda,
+          levelBonusTable: levelBonusTablePda,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([admin])
+        .rpc();
+    const updateTable = (bonuses: number[]) =>
+      program.methods
+        .adminUpdateLevelBonusTable(bonuses)
+        .accounts({
+          admin: admin.publicKey,
+          config: configPda,
+          levelBonusTable: levelBonusTablePda,
+        })
+        .signers([admin])
+        .rpc();
+    for (const bonuses of [
+      [0, 0, 160, 340, 550, 780, 1001],
+      [0, 0, 160, 340, 550, 1000, 780],
+    ]) {
+      try {
+        await initTable(bonuses);
+        expect.fail("Expected an invalid bonus table to be rejected");
+      } catch (err) {
+        expect(`${err}`).to.include("InvalidLevelBonus");
+      }
+    }
+
+    const owner = Keypair.generate().publicKey;
+    const quoteHp = async (table: PublicKey | null) => {
+      const { events } = await program.methods
+        .quoteBuyContract(0)
+        .accounts({
+          owner,
+          config: configPda,
+          userProfile: profilePda(owner),
+          levelBonusTable: table,
+        })
+        .simulate();
+      return (events.find((e) => e.name === "BuyQuote")!.data as any).hpEffective.toNumber();
+    };
+    const builtInHp = await quoteHp(null);
+    await initTable(defaults);
+    expect(await quoteHp(levelBonusTablePda)).to.eq(builtInHp);
+
+    try {
+      await quoteHp(null);
+      expect.fail("Expected the bonus table to be required once it exists");
+    } catch (err) {
+      expect(`${err}`).to.include("LevelBonusTableRequired");
+    }
+    try {
+      await quoteHp(Keypair.generate().publicKey);
+      expect.fail("Expected a substitute bonus table account to be rejected");
+    } catch (err) {
+      expect(`${err}`).to.include("ConstraintSeeds");
+    }
+
+    // Changes apply while hashrate is active, from each owner's next settle onwards.
+    const user = await newUser();
+    await buyContractFor(user, 0, 0);
+    await sleep(1100);
+    try {
+      const networkBefore = (await program.account.config.fetch(configPda)).networkHpActive;
+      await updateTable([0, 500, 500, 500, 550, 780, 1000]);
+      const networkAfterUpdate = (await program.account.config.fetch(configPda)).networkHpActive;
+      expect(networkAfterUpdate.toString()).to.eq(networkBefore.toString());
+
+      await claimMindFor(user, 0);
+      const profile = await program.account.userMiningProfile.fetch(profilePda(user.publicKey));
+      const position = await program.account.minerPosition.fetch(positionPda(user.publicKey, 0));
+      const networkAfterClaim = (await program.account.config.fetch(configPda)).networkHpActive;
+      expect(profile.levelBonusBps).to.eq(500);
+      expect(position.levelBonusBps).to.eq(500);
+      expect(networkAfterClaim.sub(networkBefore).toString()).to.eq(
+        profile.activeHp.muln(500).divn(10_000).toString()
+      );
+    } finally {
+      await updateTable(defaults);
+    }
+    const table = await program.account.levelBonusTable.fetch(levelBonusTablePda);
+    expect(table.bonuses).to.deep.eq(defaults);
+  });
+
+  it("keeps stake locked for the configured number of days", async () => {
+    const staker = await newUser();
+    await buyContractFor(staker, 0, 0);
+    await sleep(1200);
+    await claimMindFor(staker, 0);
+    const amount = (await getTokenAmount(userMindAta(staker.publicKey))).divn(2);
+
+    const setLockDays = (days: number) =>
+      program.methods
+        .adminSetStakingLockDays(new BN(days))
+        .accounts({ admin: admin.publicKey, config: configPda })
+        .signers([admin])
+        .rpc();
+    const unstake = () =>
+      program.methods
+        .unstakeMind(amount)
+        .accounts({
+          owner: staker.publicKey,
+          config: configPda,
+          userStake: stakePda(staker.publicKey),
+          vaultAuthority,
+          stakingMindVault,
+          mindMint,
+          ownerMindAta: userMindAta(staker.publicKey),
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([staker])
+        .rpc();
+
+    await setLockDays(30);
+    try {
+      await stakeMindFor(staker, amount);
+      const stake = await program.account.userStake.fetch(stakePda(staker.publicKey));
+      expect(stake.stakeStartTs.toNumber()).to.be.greaterThan(0);
+      try {
+        await unstake();
+        expect.fail("Expected unstake to be blocked during the lock");
+      } catch (err) {
+        expect(`${err}`).to.include("StakeLockActive");
+      }
+      await warpForwardSeconds(31);
+      await unstake();
+    } finally {
+      await setLockDays(0);
+    }
+  });
+
+  it("keeps a top-up on an old stake inside the lock", async () => {
+    const staker = await newUser();
+    await buyContractFor(staker, 0, 0);
+    await sleep(1200);
+    await claimMindFor(staker, 0);
+    const amount = (await getTokenAmount(userMindAta(staker.publicKey))).divn(2);
+
+    const setLockDays = (days: number) =>
+      program.methods
+        .adminSetStakingLockDays(new BN(days))
+        .accounts({ admin: admin.publicKey, config: configPda })
+        .signers([admin])
+        .rpc();
+    const unstake = () =>
+      program.methods
+        .unstakeMind(amount)
+        .accounts({
+          owner: staker.publicKey,
+          config: configPda,
+          userStake: stakePda(staker.publicKey),
+          vaultAuthority,
+          stakingMindVault,
+          mindMint,
+          ownerMindAta: userMindAta(staker.publicKey),
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([staker])
+        .rpc();
+
+    await setLockDays(10);
+    try {
+      await stakeMindFor(staker, amount);
+      // Long past the lock, the stake's own start would average the top-up straight out of it.
+      await warpForwardSeconds(40);
+      await stakeMindFor(staker, amount);
+      try {
+        await unstake();
+        expect.fail("Expected the top-up to stay locked");
+      } catch (err) {
+        expect(`${err}`).to.include("StakeLockActive");
+      }
+      await warpForwardSeconds(6);
+      await unstake();
+    } finally {
+      await setLockDays(0);
+    }
+  });
+
+  it("reports a staking overflow for an out-of-range lock", async () => {
+    const staker = await newUser();
+    await buyContractFor(staker, 0, 0);
+    await sleep(1200);
+    await claimMindFor(staker, 0);
+    const amount = (await getTokenAmount(userMindAta(staker.publicKey))).divn(2);
+    await stakeMindFor(staker, amount);
+
+    const setLockDays = (days: BN) =>
+      program.methods
+        .adminSetStakingLockDays(days)
+        .accounts({ admin: admin.publicKey, config: configPda })
+        .signers([admin])
+        .rpc();
+
+    await setLockDays(new BN("18446744073709551615"));
+    try {
+      await program.methods
+        .unstakeMind(amount)
+        .accounts({
+          owner: staker.publicKey,
+          config: configPda,
+          userStake: stakePda(staker.publicKey),
+          vaultAuthority,
+          stakingMindVault,
+          mindMint,
+          ownerMindAta: userMindAta(staker.publicKey),
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([staker])
+        .rpc();
+      expect.fail("Expected the lock length to overflow");
+    } catch (err) {
+      expect(`${err}`).to.include("StakingMathOverflow");
+    } finally {
+      await setLockDays(new BN(0));
+    }
+  });
+
+  it("closes a deactivated position once its rewards are claimed", async () => {
+    const user = await newUser();
+    await buyContractFor(user, 0, 0);
+    const position = positionPda(user.publicKey, 0);
+    const close = () =>
+      program.methods
+        .closePosition(new BN(0))
+        .accounts({ owner: user.publicKey, config: configPda, position })
+        .signers([user])
+        .rpc();
+    try {
+      await close();
+      expect.fail("Expected an active position to stay open");
+    } catch (err) {
+      expect(`${err}`).to.include("PositionNotDeactivated");
+    }
+
+    await warpForwardSeconds(8);
+    await program.methods
+      .deactivatePosition()
+      .accounts({
+        owner: user.publicKey,
+        config: configPda,
+        position,
+        userProfile: profilePda(user.publicKey),
+        systemProgram: SystemProgram.programId,
+        levelBonusTable: levelBonusTablePda,
+      })
+      .signers([user])
+      .rpc();
+    try {
+      await close();
+      expect.fail("Expected unclaimed rewards to block the close");
+    } catch (err) {
+      expect(`${err}`).to.include("UnclaimedRewards");
+    }
+
+    await claimMindFor(user, 0);
+    const before = await getLamports(user.publicKey);
+    await close();
+    expect(await provider.connection.getAccountInfo(position)).to.eq(null);
+    expect((await getLamports(user.publicKey)).gt(before)).to.eq(true);
+  });
+
+  it("lets a delegate claim for the owner within its permissions", async () => {
+    const owner = await newUser();
+    const bot = await newUser();
+    await buyContractFor(owner, 0, 0);
+    const [delegateRecord] = PublicKey.findProgramAddressSync(
+      [Buffer.from("delegate"), owner.publicKey.toBuffer(), bot.publicKey.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .setDelegate(bot.publicKey, 0b001)
+      .accounts({
+        owner: owner.publicKey,
+        delegateRecord,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([owner])
+      .rpc();
+
+    const claimAsBot = () =>
+      program.methods
+        .claimMind()
+        .accounts({
+          owner: owner.publicKey,
+          config: configPda,
+          userProfile: profilePda(owner.publicKey),
+          position: positionPda(owner.publicKey, 0),
+          vaultAuthority,
+          mindMint,
+          userMindAta: userMindAta(owner.publicKey),
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          delegate: bot.publicKey,
+          delegateRecord,
+          levelBonusTable: levelBonusTablePda,
+        })
+        .signers([bot])
+        .rpc();
+    await sleep(1200);
+    await claimAsBot();
+    expect((await getTokenAmount(userMindAta(owner.publicKey))).toNumber()).to.be.greaterThan(0);
+
+    try {
+      await program.methods
+        .renewRig()
+        .accounts({
+          owner: owner.publicKey,
+          config: configPda,
+          userProfile: profilePda(owner.publicKey),
+          position: positionPda(owner.publicKey, 0),
+          stakingRewardVault,
+          treasuryVault,
+          systemProgram: SystemProgram.programId,
+          delegate: bot.publicKey,
+          delegateRecord,
+          levelBonusTable: levelBonusTablePda,
+        })
+        .signers([bot])
+        .rpc();
+      expect.fail("Expected renew to need the renew permission");
+    } catch (err) {
+      expect(`${err}`).to.include("DelegatePermissionDenied");
+    }
+
+    await program.methods
+      .revokeDelegate()
+      .accounts({ authority: bot.publicKey, owner: owner.publicKey, delegateRecord })
+      .signers([bot])
+      .rpc();
+    expect(await provider.connection.getAccountInfo(delegateRecord)).to.eq(null);
+    try {
+      await claimAsBot();
+      expect.fail("Expected a revoked delegate to be rejected");
+    } catch (err) {
+      expect(`${err}`).to.include("AccountNotInitialized");
+    }
+  });
+
+  it("lets users claim a badge with a merkle proof", async () => {
+    const user = await newUser();
+    const other = await newUser();
+    const sha256 = (...parts: Buffer[]) =>
+      createHash("sha256").update(Buffer.concat(parts)).digest();
+    const leaf = (owner: PublicKey, tier: number, bps: number) => {
+      const bpsLe = Buffer.alloc(2);
+      bpsLe.writeUInt16LE(bps);
+      return sha256(owner.toBuffer(), Buffer.from([tier]), bpsLe);
+    };
+    const userLeaf = leaf(user.publicKey, 2, 1500);
+    const otherLeaf = leaf(other.publicKey, 1, 500);
+    const root =
+      Buffer.compare(userLeaf, otherLeaf) <= 0
+        ? sha256(userLeaf, otherLeaf)
+        : sha256(otherLeaf, userLeaf);
+    const setRoot = (value: Buffer) =>
+      program.methods
+        .adminSetBadgeMerkleRoot([...value])
+        .accounts({ admin: admin.publicKey, config: configPda })
+        .signers([admin])
+        .rpc();
+    const claim = (tier: number, bps: number) =>
+      program.methods
+        .claimBadge(tier, bps, [[...otherLeaf]])
+        .accounts({
+          owner: user.publicKey,
+          config: configPda,
+          userProfile: profilePda(user.publicKey),
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user])
+        .rpc();
+
+    await setRoot(root);
+    try {
+      try {
+        await claim(2, 2000);
+        expect.fail("Expected a tampered bonus to fail the proof");
+      } catch (err) {
+        expect(`${err}`).to.include("InvalidMerkleProof");
+      }
+      await claim(2, 1500);
+      const profile = await program.account.userMiningProfile.fetch(profilePda(user.publicKey));
+      expect(profile.badgeTier).to.eq(2);
+      expect(profile.badgeBonusBps).to.eq(1500);
+    } finally {
+      await setRoot(Buffer.alloc(32));
+    }
+  });
+
+  it("lets a keeper deactivate positions past their grace period", async () => {
+    const stale = await newUser();
+    const active = await newUser();
+    const keeper = await newUser();
+    await buyContractFor(stale, 0, 0);
+    await warpForwardSeconds(11);
+    await buyContractFor(active, 0, 0);
+
+    const setCrankReward = (lamports: number) =>
+      program.methods
+        .adminSetCrankReward(new BN(lamports))
+        .accounts({ admin: admin.publicKey, config: configPda })
+        .signers([admin])
+        .rpc();
+    const pair = (user: Keypair) => [
+      { pubkey: positionPda(user.publicKey, 0), isWritable: true, isSigner: false },
+      { pubkey: profilePda(user.publicKey), isWritable: true, isSigner: false },
+    ];
+
+    await setCrankReward(1_000);
+    try {
+      const before = await getLamports(keeper.publicKey);
+      await program.methods
+        .crankDeactivate()
+        .accounts({
+          caller: keeper.publicKey,
+          config: configPda,
+          treasuryVault,
+          systemProgram: SystemProgram.programId,
+          levelBonusTable: levelBonusTablePda,
+        })
+        .remainingAccounts([...pair(stale), ...pair(active)])
+        .signers([keeper])
+        .rpc();
+      const after = await getLamports(keeper.publicKey);
+      expect(after.sub(before).toNumber()).to.eq(1_000);
+    } finally {
+      await setCrankReward(0);
+    }
+
+    const stalePosition = await program.account.minerPosition.fetch(
+      positionPda(stale.publicKey, 0)
+    );
+    expect(stalePosition.deactivated).to.eq(true);
+    const activePosition = await program.account.minerPosition.fetch(
+      positionPda(active.publicKey, 0)
+    );
+    expect(activePosition.deactivated).to.eq(false);
+  });
+
+  it("previews a single claim without touching state", async () => {
+    const user = await newUser();
+    await buyContractFor(user, 0, 0);
+    await sleep(1200);
+    const position = positionPda(user.publicKey, 0);
+    const before = await program.account.minerPosition.fetch(position);
+
+    const { events } = await program.methods
+      .simulateClaimMind()
+      .accounts({
+        owner: user.publicKey,
+        config: configPda,
+        userProfile: profilePda(user.publicKey),
+        position,
+        levelBonusTable: levelBonusTablePda,
+      })
+      .simulate();
+    const preview = events.find((e) => e.name === "SimulatedClaim");
+    expect(preview).to.not.be.undefined;
+    const data = preview!.data as any;
+    expect(data.position.toBase58()).to.eq(position.toBase58());
+    expect(data.pending.toNumber()).to.be.greaterThan(0);
+    expect(data.hpEffective.toNumber()).to.be.greaterThan(0);
+
+    const after = await program.account.minerPosition.fetch(position);
+    expect(after.rewardDebt.toString()).to.eq(before.rewardDebt.toString());
+  });
+
+  it("rejects contracts below the configured minimum HP", async () => {
+    const user = await newUser();
+    const setMinHp = (value: number) =>
+      program.methods
+        .adminSetMinPositionHp(new BN(value))
+        .accounts({ admin: admin.publicKey, config: configPda })
+        .signers([admin])
+        .rpc();
+
+    // The starter rig has 100 scaled HP.
+    await setMinHp(101);
+    try {
+      await buyContractFor(user, 0, 0);
+      expect.fail("Expected the starter rig to fall below the minimum");
+    } catch (err) {
+      expect(`${err}`).to.include("PositionHpTooLow");
+    } finally {
+      await setMinHp(0);
+    }
+    await buyContractFor(user, 0, 0);
+  });
+
+  it("applies the configured staking share to contract revenue", async () => {
+    const cfg = await program.account.config.fetch(configPda);
+    expect(cfg.stakingShareBps).to.eq(3000);
+
+    const setShare = (bps: number) =>
+      program.methods
+        .adminSetStakingShareBps(bps)
+        .accounts({ admin: admin.publicKey, config: configPda })
+        .signers([admin])
+        .rpc();
+    const owner = Keypair.generate().publicKey;
+    const quote = async () => {
+      const { events } = await program.methods
+        .quoteBuyContract(0)
+        .accounts({
+          owner,
+          config: configPda,
+          userProfile: profilePda(owner),
+          levelBonusTable: levelBonusTablePda,
+        })
+        .simulate();
+      return events.find((e) => e.name === "BuyQuote")!.data as any;
+    };
+
+    try {
+      await setShare(10_001);
+      expect.fail("Expected a share above 100% to be rejected");
+    } catch (err) {
+      expect(`${err}`).to.include("InvalidStakingShareBps");
+    }
+
+    try {
+      await setShare(5000);
+      const updated = await program.account.config.fetch(configPda);
+      expect(updated.stakingShareBps).to.eq(5000);
+      const data = await quote();
+      const cost = data.costBase.toNumber();
+      expect(data.stakingShare.toNumber()).to.eq(Math.floor((cost * 5000) / 10_000));
+      expect(data.stakingShare.add(data.treasuryShare).toNumber()).to.eq(cost);
+    } finally {
+      await setShare(3000);
+    }
+  });
+
+  it("books direct vault deposits before paying an XNT claim", async () => {
+    const staker = await newUser();
+    await buyContractFor(staker, 0, 0);
+    await sleep(1200);
+    await claimMindFor(staker, 0);
+    await stakeMindFor(staker, (await getTokenAmount(userMindAta(staker.publicKey))).divn(2));
+
+    await fundStakingVault(LAMPORTS_PER_SOL);
+    await program.methods
+      .rollEpoch(new BN(10))
+      .accounts({ admin: admin.publicKey, config: configPda, stakingRewardVault })
+      .rpc();
+    await sleep(2000);
+
+    const deposit = LAMPORTS_PER_SOL / 2;
+    await fundStakingVault(deposit);
+    const before = await program.account.config.fetch(configPda);
+
+    const beforeLamports = await getLamports(staker.publicKey);
+    await program.methods
+      .claimXnt()
+      .accounts({
+        owner: staker.publicKey,
+        config: configPda,
+        userProfile: profilePda(staker.publicKey),
+        userStake: stakePda(staker.publicKey),
+        stakingRewardVault,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([staker])
+      .rpc();
+    const afterLamports = await getLamports(staker.publicKey);
+    expect(afterLamports.gt(beforeLamports)).to.eq(true);
+
+    const after = await program.account.config.fetch(configPda);
+    expect(after.stakingUndistributedXnt.toNumber()).to.be.at.least(
+      before.stakingUndistributedXnt.toNumber() + deposit
+    );
+  });
 });
 });